@@ -0,0 +1,365 @@
+// Opt-in local HTTP API for scripts and other automation
+// Listens on 127.0.0.1 only; every request needs the token from `api-token` in the config dir,
+// sent as `Authorization: Bearer <token>`
+//
+//   GET    /api/downloads             running downloads
+//   POST   /api/downloads             start one download (same fields as download_video)
+//   DELETE /api/downloads/{id}        cancel a download
+//   POST   /api/downloads/{id}/stop   stop a live recording
+//   POST   /api/queue                 fetch a URL and download everything in it
+//   POST   /api/metadata              fetch metadata (same fields as fetch_metadata)
+//   GET    /api/history               finished downloads, newest first
+//   GET    /api/events                server-sent events mirroring `download-progress`
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, Listener, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+use crate::{history, settings, DownloadOptions};
+
+const TOKEN_FILE: &str = "api-token";
+
+/// Bodies larger than this are refused
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// Comment line sent on idle event streams so dead clients are noticed
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How quickly open event streams notice that the server was stopped
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct RunningServer {
+    port: u16,
+    server: Arc<Server>,
+    /// Set when the server is stopped, so open event streams close too
+    stopped: Arc<AtomicBool>,
+}
+
+static SERVER: Lazy<Mutex<Option<RunningServer>>> = Lazy::new(|| Mutex::new(None));
+
+/// What a request asks for, see the list at the top
+#[derive(Debug, PartialEq)]
+enum Route {
+    Events,
+    Downloads,
+    History,
+    Metadata,
+    StartDownload,
+    Queue,
+    CancelDownload(String),
+    StopRecording(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetadataRequest {
+    url: String,
+    force_refresh: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DownloadRequest {
+    url: String,
+    download_type: Option<String>,
+    output_path: Option<String>,
+    title: Option<String>,
+    video_quality: Option<String>,
+    options: Option<DownloadOptions>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueueRequest {
+    url: String,
+    download_type: Option<String>,
+    video_quality: Option<String>,
+}
+
+fn token_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?
+        .join(TOKEN_FILE))
+}
+
+/// Read the API token, creating a random one on first use
+fn load_or_create_token(app: &AppHandle) -> Result<String, String> {
+    let path = token_path(app)?;
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim().to_string();
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    fs::write(&path, &token)
+        .map_err(|e| format!("Failed to write API token: {}", e))?;
+
+    // Only the current user may read the token
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+    }
+
+    println!("api: Created token at {:?}", path);
+    Ok(token)
+}
+
+/// Start, stop or restart the server to match the current settings
+pub fn apply(app: &AppHandle) -> Result<(), String> {
+    let settings = settings::get();
+    let mut server = SERVER.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    if let Some(running) = server.as_ref() {
+        if settings.api_enabled && running.port == settings.api_port {
+            return Ok(());
+        }
+        println!("api: Stopping server on port {}", running.port);
+        running.server.unblock();
+        running.stopped.store(true, Ordering::SeqCst);
+        *server = None;
+    }
+
+    if !settings.api_enabled {
+        return Ok(());
+    }
+
+    let token = load_or_create_token(app)?;
+    let listener = Arc::new(Server::http(("127.0.0.1", settings.api_port))
+        .map_err(|e| format!("Failed to start API server on port {}: {}", settings.api_port, e))?);
+    println!("api: Listening on http://127.0.0.1:{}", settings.api_port);
+
+    let app = app.clone();
+    let incoming = listener.clone();
+    let stopped = Arc::new(AtomicBool::new(false));
+    let server_stopped = stopped.clone();
+    std::thread::spawn(move || {
+        for request in incoming.incoming_requests() {
+            let app = app.clone();
+            let token = token.clone();
+            let stopped = server_stopped.clone();
+            std::thread::spawn(move || handle_request(&app, &token, &stopped, request));
+        }
+    });
+
+    *server = Some(RunningServer { port: settings.api_port, server: listener, stopped });
+    Ok(())
+}
+
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let expected = format!("Bearer {}", token);
+    request.headers().iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .is_some_and(|header| {
+            // Compare every byte so the time taken doesn't reveal the matching prefix
+            let given = header.value.as_str().as_bytes();
+            given.len() == expected.len()
+                && given.iter().zip(expected.as_bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+        })
+}
+
+fn json_response(status: u16, body: &Value) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("valid header"))
+}
+
+fn read_body<T: for<'de> Deserialize<'de>>(request: &mut Request) -> Result<T, String> {
+    let mut body = Vec::new();
+    Read::take(request.as_reader(), MAX_BODY_BYTES + 1).read_to_end(&mut body)
+        .map_err(|e| format!("Failed to read request body: {}", e))?;
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err("Request body is too large".to_string());
+    }
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid request body: {}", e))
+}
+
+/// Match a request to its route, ignoring the query string
+fn route(method: &Method, url: &str) -> Option<Route> {
+    let path = url.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        (Method::Get, ["api", "events"]) => Some(Route::Events),
+        (Method::Get, ["api", "downloads"]) => Some(Route::Downloads),
+        (Method::Get, ["api", "history"]) => Some(Route::History),
+        (Method::Post, ["api", "metadata"]) => Some(Route::Metadata),
+        (Method::Post, ["api", "downloads"]) => Some(Route::StartDownload),
+        (Method::Post, ["api", "queue"]) => Some(Route::Queue),
+        (Method::Delete, ["api", "downloads", id]) if !id.is_empty() => Some(Route::CancelDownload(id.to_string())),
+        (Method::Post, ["api", "downloads", id, "stop"]) if !id.is_empty() => Some(Route::StopRecording(id.to_string())),
+        _ => None,
+    }
+}
+
+fn handle_request(app: &AppHandle, token: &str, stopped: &AtomicBool, mut request: Request) {
+    if !is_authorized(&request, token) {
+        let _ = request.respond(json_response(401, &serde_json::json!({ "error": "Missing or invalid token" })));
+        return;
+    }
+
+    println!("api: {} {}", request.method(), request.url().split('?').next().unwrap_or_default());
+    let Some(route) = route(request.method(), request.url()) else {
+        let _ = request.respond(json_response(404, &serde_json::json!({ "error": "Not found" })));
+        return;
+    };
+
+    let result = match route {
+        Route::Events => {
+            stream_events(app, stopped, request);
+            return;
+        }
+        Route::Downloads => Ok(serde_json::json!(history::active())),
+        Route::History => Ok(serde_json::json!(history::history())),
+        Route::Metadata => read_body::<MetadataRequest>(&mut request).and_then(|body| {
+            tauri::async_runtime::block_on(crate::fetch_metadata(app.clone(), body.url, body.force_refresh, None))
+                .map(|info| serde_json::json!(info))
+        }),
+        Route::StartDownload => read_body::<DownloadRequest>(&mut request).and_then(|body| {
+            start_download(app, body).map(|id| serde_json::json!({ "id": id }))
+        }),
+        Route::Queue => read_body::<QueueRequest>(&mut request).and_then(|body| {
+            tauri::async_runtime::block_on(crate::queue_url(app.clone(), body.url, body.download_type, body.video_quality))
+                .map(|ids| serde_json::json!({ "ids": ids }))
+        }),
+        Route::CancelDownload(id) => {
+            tauri::async_runtime::block_on(crate::cancel_download(app.clone(), id))
+                .map(|cancelled| serde_json::json!({ "cancelled": cancelled }))
+        }
+        Route::StopRecording(id) => {
+            tauri::async_runtime::block_on(crate::stop_recording(id))
+                .map(|stopping| serde_json::json!({ "stopping": stopping }))
+        }
+    };
+
+    let response = match result {
+        Ok(body) => json_response(200, &body),
+        Err(e) => json_response(400, &serde_json::json!({ "error": e, "category": crate::errors::classify(&e) })),
+    };
+    let _ = request.respond(response);
+}
+
+/// download_video with the configured defaults for anything left out
+fn start_download(app: &AppHandle, body: DownloadRequest) -> Result<String, String> {
+    let settings = settings::get();
+    let output_path = match body.output_path {
+        Some(path) => path,
+        None => crate::default_download_folder(app)?,
+    };
+
+    tauri::async_runtime::block_on(crate::download_video(
+        app.clone(),
+        body.url.clone(),
+        body.download_type.unwrap_or(settings.default_download_type),
+        output_path,
+        body.title.unwrap_or(body.url),
+        body.video_quality.unwrap_or(settings.default_video_quality),
+        body.options,
+    ))
+}
+
+/// Forward every `download-progress` payload until the client disconnects or the server stops
+fn stream_events(app: &AppHandle, stopped: &AtomicBool, request: Request) {
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    if writer.write_all(head.as_bytes()).and_then(|_| writer.flush()).is_err() {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel::<String>();
+    let listener = app.listen_any("download-progress", move |event| {
+        let _ = sender.send(event.payload().to_string());
+    });
+
+    let mut last_write = Instant::now();
+    while !stopped.load(Ordering::SeqCst) {
+        let message = match receiver.recv_timeout(STREAM_POLL_INTERVAL) {
+            Ok(payload) => format!("event: download-progress\ndata: {}\n\n", payload),
+            Err(mpsc::RecvTimeoutError::Timeout) if last_write.elapsed() >= KEEPALIVE_INTERVAL => ": keepalive\n\n".to_string(),
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        if writer.write_all(message.as_bytes()).and_then(|_| writer.flush()).is_err() {
+            break;
+        }
+        last_write = Instant::now();
+    }
+
+    app.unlisten(listener);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_http::TestRequest;
+
+    fn request(authorization: Option<&str>) -> Request {
+        let request = TestRequest::new().with_method(Method::Get).with_path("/api/downloads");
+        match authorization {
+            Some(value) => request.with_header(Header::from_bytes("Authorization", value).unwrap()).into(),
+            None => request.into(),
+        }
+    }
+
+    #[test]
+    fn requires_token() {
+        let token = "0123456789abcdef0123456789abcdef";
+        let cases = [
+            (None, false),
+            (Some(""), false),
+            (Some("Bearer"), false),
+            (Some("Bearer "), false),
+            (Some("Bearer 0123456789abcdef0123456789abcdee"), false),
+            (Some("Bearer 0123456789abcdef"), false),
+            (Some("Bearer 0123456789abcdef0123456789abcdef0"), false),
+            (Some("Basic 0123456789abcdef0123456789abcdef"), false),
+            (Some("0123456789abcdef0123456789abcdef"), false),
+            (Some("Bearer 0123456789abcdef0123456789abcdef"), true),
+        ];
+
+        for (authorization, expected) in cases {
+            assert_eq!(is_authorized(&request(authorization), token), expected, "authorization {:?}", authorization);
+        }
+    }
+
+    #[test]
+    fn routes_requests() {
+        let cases = [
+            (Method::Get, "/api/events", Some(Route::Events)),
+            (Method::Get, "/api/downloads", Some(Route::Downloads)),
+            (Method::Get, "/api/downloads/", Some(Route::Downloads)),
+            (Method::Get, "/api/history?limit=10", Some(Route::History)),
+            (Method::Post, "/api/metadata", Some(Route::Metadata)),
+            (Method::Post, "/api/downloads", Some(Route::StartDownload)),
+            (Method::Post, "/api/queue", Some(Route::Queue)),
+            (Method::Delete, "/api/downloads/abc-123", Some(Route::CancelDownload("abc-123".to_string()))),
+            (Method::Post, "/api/downloads/abc-123/stop", Some(Route::StopRecording("abc-123".to_string()))),
+            (Method::Get, "/", None),
+            (Method::Get, "/api", None),
+            (Method::Post, "/api/events", None),
+            (Method::Put, "/api/downloads", None),
+            (Method::Delete, "/api/downloads", None),
+            (Method::Delete, "/api/downloads//", None),
+            (Method::Get, "/api/downloads/abc-123/stop", None),
+            (Method::Post, "/api/downloads/abc-123/pause", None),
+            (Method::Get, "/API/downloads", None),
+        ];
+
+        for (method, url, expected) in cases {
+            assert_eq!(route(&method, url), expected, "route for {} {}", method, url);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+// Automatic yt-dlp updates
+// - After downloads fail with a bot check or a broken extractor, both usually fixed by a newer
+//   yt-dlp release. A newer release is looked for at most once per configured interval and
+//   only while no other download is running; when one was installed the failed job is started again
+// - On a schedule in the background, announcing new releases as `ytdlp-update-available`
+//   and installing them right away when configured to
+// Progress is reported as `ytdlp-auto-update` events
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::errors::ErrorCategory;
+use crate::host::Host;
+use crate::{install, settings, YtdlpSource};
+
+const STATE_FILE: &str = "auto-update.json";
+
+/// How often the background task looks at whether a scheduled check is due
+const SCHEDULE_TICK: Duration = Duration::from_secs(10 * 60);
+
+/// First scheduled check after startup, so it doesn't compete with the first yt-dlp install
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// When yt-dlp was last checked and last updated (Unix seconds), kept across restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+    last_check: u64,
+    last_update: u64,
+    last_scheduled_check: u64,
+}
+
+// Checks wait for each other, so failing downloads of a playlist don't all check at once
+static CHECK_LOCK: Mutex<()> = Mutex::new(());
+
+fn state_path(app: &impl Host) -> Result<PathBuf, String> {
+    Ok(app.local_data_dir()?.join(STATE_FILE))
+}
+
+fn load_state(app: &impl Host) -> State {
+    state_path(app).ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(app: &impl Host, state: &State) {
+    let result = state_path(app).and_then(|path| {
+        let contents = serde_json::to_string(state).map_err(|e| e.to_string())?;
+        fs::write(path, contents).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        eprintln!("auto_update: Failed to save state: {}", e);
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn emit(app: &impl Host, download_id: Option<&str>, status: &str, message: &str) {
+    app.emit_event("ytdlp-auto-update", serde_json::json!({
+        "downloadId": download_id,
+        "status": status,
+        "message": message,
+    }));
+}
+
+/// Update yt-dlp after a download failed with `category`, if the policy allows
+/// Returns true when a newer yt-dlp is installed than the one the download started with
+pub fn update_after_failure(app: &impl Host, download_id: &str, category: ErrorCategory, started_at: SystemTime) -> bool {
+    if !matches!(category, ErrorCategory::BotCheck | ErrorCategory::ExtractorBroken) {
+        return false;
+    }
+
+    let settings = settings::get();
+    // A mirror without an update feed has nothing to check against
+    if !settings.ytdlp_auto_update || !install::can_check_latest(&settings) {
+        return false;
+    }
+
+    // A yt-dlp from PATH or a custom file is updated by whoever installed it
+    match crate::resolve_ytdlp(app) {
+        Ok((_, YtdlpSource::Bundled | YtdlpSource::Managed)) => {}
+        _ => return false,
+    }
+
+    let _guard = CHECK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_state(app);
+    let now = unix_secs(SystemTime::now());
+
+    // Another failed download already updated yt-dlp after this one started
+    if state.last_update >= unix_secs(started_at) {
+        println!("auto_update: yt-dlp was updated since download {} started", download_id);
+        return true;
+    }
+
+    let interval = Duration::from_secs(settings.ytdlp_auto_update_interval_hours * 3600);
+    if now.saturating_sub(state.last_check) < interval.as_secs() {
+        println!("auto_update: Checked less than {}h ago, not checking again", settings.ytdlp_auto_update_interval_hours);
+        return false;
+    }
+
+    // Replacing yt-dlp under a running download can break it; the next failure checks again
+    if crate::other_downloads_active(download_id) {
+        println!("auto_update: Other downloads running, not updating yt-dlp for {}", download_id);
+        return false;
+    }
+
+    state.last_check = now;
+    save_state(app, &state);
+
+    emit(app, Some(download_id), "checking", "Download failed, checking for a newer yt-dlp");
+    let installed = crate::get_ytdlp_path(app).ok().and_then(|path| crate::installed_ytdlp_version(&path));
+    let report = install::version_report(installed, &settings);
+    if !report.update_available {
+        emit(app, Some(download_id), "up-to-date", "No newer yt-dlp available");
+        return false;
+    }
+
+    let target = report.pinned.or(report.latest).unwrap_or_default();
+    emit(app, Some(download_id), "updating", &format!("Updating yt-dlp to {}", target));
+    match crate::update_ytdlp_binary(app) {
+        Ok(_) => {
+            let updated = crate::get_ytdlp_path(app).ok().and_then(|path| crate::installed_ytdlp_version(&path));
+            if updated.is_none() || updated == report.installed {
+                emit(app, Some(download_id), "failed", "yt-dlp was not updated");
+                return false;
+            }
+
+            state.last_update = unix_secs(SystemTime::now());
+            save_state(app, &state);
+            emit(app, Some(download_id), "updated", &format!(
+                "Updated yt-dlp to {}, retrying the download",
+                updated.unwrap_or_default()
+            ));
+            true
+        }
+        Err(e) => {
+            eprintln!("auto_update: {}", e);
+            emit(app, Some(download_id), "failed", &e);
+            false
+        }
+    }
+}
+
+/// Start the background task for scheduled update checks
+pub fn start(app: impl Host) {
+    std::thread::spawn(move || {
+        std::thread::sleep(STARTUP_DELAY);
+        loop {
+            scheduled_check(&app);
+            std::thread::sleep(SCHEDULE_TICK);
+        }
+    });
+}
+
+fn scheduled_check(app: &impl Host) {
+    // Settings are read on every tick, so changes apply without a restart
+    let settings = settings::get();
+    // A mirror without an update feed has nothing to check against
+    if !settings.ytdlp_update_check || !install::can_check_latest(&settings) {
+        return;
+    }
+    match crate::resolve_ytdlp(app) {
+        Ok((_, YtdlpSource::Bundled | YtdlpSource::Managed)) => {}
+        _ => return,
+    }
+
+    let _guard = CHECK_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_state(app);
+    let now = unix_secs(SystemTime::now());
+    if now.saturating_sub(state.last_scheduled_check) < settings.ytdlp_update_check_interval_hours * 3600 {
+        return;
+    }
+
+    let auto_install = settings.ytdlp_update_install == "auto";
+
+    // Replacing yt-dlp under a running download can break it, installing waits for a quiet moment
+    if auto_install && crate::downloads_active() {
+        println!("auto_update: Downloads running, postponing the scheduled update check");
+        return;
+    }
+
+    let installed = crate::get_ytdlp_path(app).ok().and_then(|path| crate::installed_ytdlp_version(&path));
+    let report = install::version_report(installed, &settings);
+    if report.latest.is_none() && report.pinned.is_none() {
+        // The feed couldn't be reached, try again on the next tick
+        return;
+    }
+
+    state.last_scheduled_check = now;
+    save_state(app, &state);
+
+    if !report.update_available {
+        println!("auto_update: yt-dlp {} is up to date", report.installed.as_deref().unwrap_or("unknown"));
+        return;
+    }
+
+    println!("auto_update: yt-dlp {:?} is available", report.pinned.as_ref().or(report.latest.as_ref()));
+    app.emit_event("ytdlp-update-available", serde_json::json!({
+        "installed": report.installed,
+        "latest": report.latest,
+        "channel": report.channel,
+        "pinned": report.pinned,
+        "autoInstall": auto_install,
+    }));
+    if !auto_install {
+        return;
+    }
+
+    emit(app, None, "updating", "Installing the new yt-dlp release");
+    match crate::update_ytdlp_binary(app) {
+        Ok(message) => {
+            state.last_update = unix_secs(SystemTime::now());
+            save_state(app, &state);
+            emit(app, None, "updated", message.lines().next().unwrap_or_default());
+        }
+        Err(e) => {
+            eprintln!("auto_update: {}", e);
+            emit(app, None, "failed", &e);
+        }
+    }
+}
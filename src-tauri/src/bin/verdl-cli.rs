@@ -0,0 +1,4 @@
+// Headless entry point, see cli.rs
+fn main() {
+    std::process::exit(verdl_lib::run_cli(std::env::args().skip(1).collect()))
+}
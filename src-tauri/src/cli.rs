@@ -0,0 +1,357 @@
+// Headless command-line mode (verdl-cli) for cron jobs and SSH sessions
+// Shares settings, the managed yt-dlp and all URL/option validation with the app.
+// Results go to stdout, progress to stderr; the exit code is the error category's
+
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use serde_json::Value;
+use crate::errors::{self, ErrorCategory};
+use crate::host::Host;
+use crate::{settings, DownloadOptions};
+
+const USAGE: &str = "\
+Usage: verdl-cli <command> [options]
+
+Commands:
+  info <url>         Print metadata for a video, playlist or channel
+      --json           Print the full metadata as JSON
+      --refresh        Ignore cached metadata
+  download <url>     Download a video, or every video in a playlist or channel
+      -a, --audio      Audio only
+      -q, --quality Q  best, 4k, 1080p, 720p or 480p (default from settings)
+      -o, --output DIR Output folder (default from settings, else ~/Downloads)
+      --live MODE      Record a livestream from the 'start' or from 'now'
+  update             Update yt-dlp, restoring the previous version if the new one fails
+  rollback [VERSION] List the yt-dlp versions kept for rollback, or restore one
+  version            Show the installed and latest yt-dlp version
+  help               Show this help
+
+Exit codes:
+  0 success, 1 other error, 2 invalid input, 3 network error, 4 bot check,
+  5 video unavailable, 6 extractor broken, 7 yt-dlp missing, 8 filesystem error";
+
+/// The CLI's environment: same directories as the app, events are passed to the command loop
+#[derive(Clone)]
+struct CliHost {
+    identifier: String,
+    events: mpsc::Sender<(String, Value)>,
+}
+
+impl Host for CliHost {
+    fn emit_event(&self, event: &str, payload: Value) {
+        if event == "metadata-entry" {
+            eprint!("\rRetrieving metadata... {} videos", payload["count"]);
+            return;
+        }
+        if event == "ytdlp-auto-update" {
+            eprintln!("\n{}", payload["message"].as_str().unwrap_or_default());
+            return;
+        }
+        if event == "js-runtime-install-progress" {
+            match payload["status"].as_str() {
+                Some("downloading") => eprint!("\rInstalling Deno... {:.0}%", payload["progress"].as_f64().unwrap_or(0.0)),
+                Some("installed") => eprintln!("\nInstalled Deno"),
+                Some("failed") => eprintln!("\nFailed to install Deno, YouTube downloads may fail"),
+                _ => {}
+            }
+            return;
+        }
+        let _ = self.events.send((event.to_string(), payload));
+    }
+
+    // The CLI is installed next to the app binary
+    fn resource_dir(&self) -> Result<PathBuf, String> {
+        std::env::current_exe()
+            .map_err(|e| format!("Failed to get resource dir: {}", e))?
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .ok_or_else(|| "Failed to get resource dir".to_string())
+    }
+
+    fn local_data_dir(&self) -> Result<PathBuf, String> {
+        dirs::data_local_dir()
+            .map(|dir| dir.join(&self.identifier))
+            .ok_or_else(|| "Failed to get local data dir".to_string())
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        dirs::config_dir()
+            .map(|dir| dir.join(&self.identifier))
+            .ok_or_else(|| "Failed to get config dir".to_string())
+    }
+}
+
+/// Arguments after the command name
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        const WITH_VALUE: [&str; 5] = ["-q", "--quality", "-o", "--output", "--live"];
+        const SWITCHES: [&str; 4] = ["-a", "--audio", "--json", "--refresh"];
+
+        let mut parsed = Args { positional: Vec::new(), flags: Vec::new() };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if WITH_VALUE.contains(&arg.as_str()) {
+                let value = args.next().ok_or_else(|| format!("Invalid option: {} needs a value", arg))?;
+                parsed.flags.push((arg.clone(), Some(value.clone())));
+            } else if SWITCHES.contains(&arg.as_str()) {
+                parsed.flags.push((arg.clone(), None));
+            } else if arg.starts_with('-') {
+                return Err(format!("Invalid option: {}", arg));
+            } else {
+                parsed.positional.push(arg.clone());
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn has(&self, names: &[&str]) -> bool {
+        self.flags.iter().any(|(name, _)| names.contains(&name.as_str()))
+    }
+
+    fn value(&self, names: &[&str]) -> Option<String> {
+        self.flags.iter().rev()
+            .find(|(name, _)| names.contains(&name.as_str()))
+            .and_then(|(_, value)| value.clone())
+    }
+
+    fn url(&self) -> Result<String, String> {
+        match self.positional.as_slice() {
+            [url] => Ok(url.clone()),
+            [] => Err("Invalid arguments: missing URL".to_string()),
+            _ => Err("Invalid arguments: expected a single URL".to_string()),
+        }
+    }
+}
+
+/// Run the CLI with the arguments after the program name, returns the process exit code
+pub fn run(args: Vec<String>) -> i32 {
+    #[cfg(target_os = "windows")]
+    if let Some(code) = crate::run_interrupt_helper(&args) {
+        return code;
+    }
+
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ErrorCategory::InvalidInput.exit_code();
+    };
+
+    // Same identifier as the app, so both use the same settings and yt-dlp
+    let config: Value = serde_json::from_str(include_str!("../tauri.conf.json")).unwrap_or_default();
+    let (sender, receiver) = mpsc::channel();
+    let host = CliHost {
+        identifier: config["identifier"].as_str().unwrap_or("com.verdl.downloader").to_string(),
+        events: sender,
+    };
+
+    if let Err(e) = settings::load(&host) {
+        eprintln!("Failed to load settings: {}", e);
+    }
+
+    // There's no background task to install the JavaScript runtime in, do it before yt-dlp runs
+    if matches!(command.as_str(), "info" | "download") {
+        crate::js_runtime::install(&host);
+    }
+
+    let result = Args::parse(rest).and_then(|args| match command.as_str() {
+        "info" => info(&host, &args),
+        "download" => download(&host, &args, &receiver),
+        "update" => crate::update_ytdlp_binary(&host).map(|message| println!("{}", message.trim())),
+        "rollback" => rollback(&host, &args),
+        "version" => {
+            version(&host);
+            Ok(())
+        }
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("Invalid command '{}'. Run verdl-cli help for usage.", other)),
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e.trim());
+            errors::classify(&e).exit_code()
+        }
+    }
+}
+
+fn version(host: &CliHost) {
+    let installed = crate::get_ytdlp_path(host).ok().and_then(|path| crate::installed_ytdlp_version(&path));
+    let report = crate::install::version_report(installed, &settings::get());
+
+    println!("verdl-cli {}", env!("CARGO_PKG_VERSION"));
+    println!("yt-dlp installed: {}", report.installed.as_deref().unwrap_or("unknown"));
+    println!("yt-dlp latest ({}): {}", report.channel, report.latest.as_deref().unwrap_or("unknown"));
+    if let Some(pinned) = &report.pinned {
+        println!("yt-dlp pinned: {}", pinned);
+    }
+    if report.update_available {
+        println!("Run verdl-cli update to install {}", report.pinned.or(report.latest).unwrap_or_default());
+    }
+}
+
+fn rollback(host: &CliHost, args: &Args) -> Result<(), String> {
+    match args.positional.as_slice() {
+        [] => {
+            let installed = crate::get_ytdlp_path(host).ok().and_then(|path| crate::installed_ytdlp_version(&path));
+            for saved in crate::update::list_versions(&host.local_data_dir()?, installed.as_deref()) {
+                println!("{}{}", saved.version, if saved.current { "  (installed)" } else { "" });
+            }
+            Ok(())
+        }
+        [version] => crate::rollback_ytdlp_binary(host, version).map(|message| println!("{}", message)),
+        _ => Err("Invalid arguments: expected a single version".to_string()),
+    }
+}
+
+fn info(host: &CliHost, args: &Args) -> Result<(), String> {
+    let url = args.url()?;
+    let metadata = tauri::async_runtime::block_on(
+        crate::load_metadata(host, url, Some(args.has(&["--refresh"])), None),
+    )?;
+    eprintln!();
+
+    if args.has(&["--json"]) {
+        let json = serde_json::to_string_pretty(&metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("{}", metadata.title);
+    if let Some(uploader) = &metadata.uploader {
+        println!("by {}", uploader);
+    }
+    println!("{} video(s)", metadata.video_count);
+    for video in &metadata.videos {
+        println!("  {}  [{}]  {}", video.url, video.duration.as_deref().unwrap_or("--:--"), video.title);
+    }
+    Ok(())
+}
+
+fn download(host: &CliHost, args: &Args, events: &mpsc::Receiver<(String, Value)>) -> Result<(), String> {
+    let url = args.url()?;
+    let settings = settings::get();
+    let download_type = if args.has(&["-a", "--audio"]) {
+        "audio".to_string()
+    } else {
+        settings.default_download_type
+    };
+    let video_quality = args.value(&["-q", "--quality"]).unwrap_or(settings.default_video_quality);
+    let output_path = match args.value(&["-o", "--output"]) {
+        Some(path) => path,
+        None => crate::default_download_folder(host)?,
+    };
+    let options = DownloadOptions {
+        live_mode: args.value(&["--live"]),
+        ..Default::default()
+    };
+
+    let metadata = tauri::async_runtime::block_on(crate::load_metadata(host, url, None, None))?;
+    eprintln!();
+
+    // One at a time: the first failure decides the exit code, the rest still get downloaded
+    let total = metadata.videos.len();
+    let mut failed = 0;
+    let mut first_error = None;
+    for (index, video) in metadata.videos.iter().enumerate() {
+        let label = format!("[{}/{}] {}", index + 1, total, video.title);
+        let result = tauri::async_runtime::block_on(crate::start_download(
+            host,
+            video.url.clone(),
+            download_type.clone(),
+            output_path.clone(),
+            video.title.clone(),
+            video_quality.clone(),
+            Some(options.clone()),
+        )).and_then(|id| wait_for_download(&id, &label, events));
+
+        if let Err(e) = result {
+            eprintln!("{}: failed: {}", label, e.trim());
+            failed += 1;
+            first_error.get_or_insert(e);
+        }
+    }
+
+    match first_error {
+        Some(e) if total > 1 => Err(format!("{} of {} downloads failed. First error: {}", failed, total, e)),
+        Some(e) => Err(e),
+        None => {
+            println!("Downloaded {} video(s) to {}", total, output_path);
+            Ok(())
+        }
+    }
+}
+
+/// How often wait_for_download checks that a quiet download is still running
+const MONITOR_POLL: Duration = Duration::from_secs(1);
+
+/// Print progress for a download until it finishes
+fn wait_for_download(id: &str, label: &str, events: &mpsc::Receiver<(String, Value)>) -> Result<(), String> {
+    let interactive = std::io::stderr().is_terminal();
+    let mut last_reported = -1.0;
+
+    loop {
+        let (event, payload) = match events.recv_timeout(MONITOR_POLL) {
+            Ok(received) => received,
+            // The monitor reports how a download ended before unregistering it, so one that
+            // is gone with nothing left to read stopped without saying so
+            Err(mpsc::RecvTimeoutError::Timeout) if crate::download_active(id) => continue,
+            Err(mpsc::RecvTimeoutError::Timeout) => match events.try_recv() {
+                Ok(received) => received,
+                Err(_) => break,
+            },
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        if event != "download-progress" || payload["id"].as_str() != Some(id) {
+            continue;
+        }
+
+        match payload["status"].as_str().unwrap_or_default() {
+            "completed" => {
+                eprintln!("{}{}: done", if interactive { "\r" } else { "" }, label);
+                return Ok(());
+            }
+            "error" => {
+                if interactive {
+                    eprintln!();
+                }
+                return Err(payload["error"].as_str().unwrap_or("Download failed").to_string());
+            }
+            "cancelled" => return Err("Download cancelled".to_string()),
+            "downloading" => {
+                let progress = payload["progress"].as_f64().unwrap_or(0.0);
+                if interactive {
+                    eprint!("\r{}: {:5.1}%", label, progress);
+                    let _ = std::io::stderr().flush();
+                } else if progress - last_reported >= 10.0 {
+                    // Logs (cron, pipes) get a line every 10%
+                    eprintln!("{}: {:.0}%", label, progress);
+                    last_reported = progress;
+                }
+            }
+            "recording" | "waiting" | "stopping" => {
+                let status = payload["status"].as_str().unwrap_or_default();
+                let elapsed = payload["elapsed"].as_u64().unwrap_or(0);
+                let bytes = payload["recordedBytes"].as_u64().unwrap_or(0);
+                if interactive {
+                    eprint!("\r{}: {} {}s, {:.1} MB", label, status, elapsed, bytes as f64 / 1_048_576.0);
+                    let _ = std::io::stderr().flush();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err("Download monitor stopped unexpectedly".to_string())
+}
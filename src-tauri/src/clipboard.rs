@@ -0,0 +1,86 @@
+// Opt-in clipboard watcher
+// Polls the clipboard and offers (or, in auto mode, queues) supported URLs as they are copied
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use crate::settings;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Clipboard contents longer than this are not URLs we care about
+const MAX_CLIPBOARD_LENGTH: usize = 2048;
+
+// Runtime pause toggle, separate from the persistent on/off setting
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+    println!("clipboard: Watcher {}", if paused { "paused" } else { "resumed" });
+}
+
+/// Start the watcher thread; it idles while the setting is off or the watcher is paused
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        // Whatever is on the clipboard at startup (or when watching is switched on) isn't offered
+        let mut last_text: Option<String> = None;
+        let mut offered = HashSet::new();
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let settings = settings::get();
+            if !settings.clipboard_watch || PAUSED.load(Ordering::Relaxed) {
+                last_text = None;
+                continue;
+            }
+
+            let Ok(text) = app.clipboard().read_text() else {
+                continue;
+            };
+            let text = text.trim().to_string();
+
+            let is_first_read = last_text.is_none();
+            if last_text.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+            last_text = Some(text.clone());
+
+            if is_first_read || text.is_empty() || text.len() > MAX_CLIPBOARD_LENGTH {
+                continue;
+            }
+
+            let Ok(parsed) = crate::parse_supported_url(&app, &text) else {
+                continue;
+            };
+
+            // Copying the same video again (in any URL form) is not offered twice
+            if !offered.insert(parsed.dedup_key()) {
+                continue;
+            }
+
+            println!("clipboard: Found {:?} URL {}", parsed.kind, parsed.canonical_url);
+            let _ = app.emit("clipboard-url", serde_json::json!({
+                "url": parsed.canonical_url,
+                "kind": parsed.kind,
+                "queued": settings.clipboard_auto_queue,
+            }));
+
+            if settings.clipboard_auto_queue {
+                let app = app.clone();
+                let url = parsed.canonical_url;
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::queue_url(app.clone(), url.clone(), None, None).await {
+                        let _ = app.emit("download-error", serde_json::json!({
+                            "url": url,
+                            "category": crate::errors::classify(&e),
+                            "error": e,
+                        }));
+                    }
+                });
+            }
+        }
+    });
+}
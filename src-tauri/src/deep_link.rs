@@ -0,0 +1,198 @@
+// `verdl://` links for starting downloads from other tools
+// e.g. verdl://download?url=https%3A%2F%2Fyoutu.be%2FID&quality=720p&type=audio
+// A second launch with a link is forwarded to the running instance by the single-instance plugin
+// Any web page can open such a link, so nothing is queued until the user confirms it
+
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+pub const SCHEME: &str = "verdl";
+
+const QUALITIES: [&str; 5] = ["best", "4k", "1080p", "720p", "480p"];
+
+/// A parsed `verdl://download` link
+#[derive(Debug, Clone, PartialEq)]
+struct DownloadLink {
+    url: String,
+    download_type: Option<String>,
+    video_quality: Option<String>,
+}
+
+/// Parse and validate a link; its url has to be from one of `allowed_sites`
+fn parse(link: &Url, allowed_sites: &[String]) -> Result<DownloadLink, String> {
+    if link.scheme() != SCHEME {
+        return Err(format!("Not a {}:// link", SCHEME));
+    }
+    // verdl://download?... puts the action in the host, verdl:download?... in the path
+    let action = link.host_str().unwrap_or_else(|| link.path().trim_matches('/'));
+    if action != "download" {
+        return Err(format!("Unknown link action '{}'", action));
+    }
+
+    let param = |name: &str| {
+        link.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    let url = param("url").ok_or("Link is missing the url parameter")?;
+    let url = crate::urls::parse_allowed(&url, allowed_sites)
+        .map(|parsed| parsed.canonical_url)
+        .ok_or_else(|| format!("Invalid URL. Only URLs from allowed sites ({}) are allowed.", allowed_sites.join(", ")))?;
+
+    let video_quality = param("quality");
+    if let Some(quality) = video_quality.as_deref().filter(|q| !QUALITIES.contains(q)) {
+        return Err(format!("Invalid quality '{}'. Expected one of: {}", quality, QUALITIES.join(", ")));
+    }
+
+    let download_type = param("type");
+    if let Some(download_type) = download_type.as_deref().filter(|t| !["video", "audio"].contains(t)) {
+        return Err(format!("Invalid download type '{}'. Expected 'video' or 'audio'.", download_type));
+    }
+
+    Ok(DownloadLink { url, download_type, video_quality })
+}
+
+/// Validate a link, ask the user and queue its download, reporting failures to the frontend
+fn handle(app: &AppHandle, link: &Url) {
+    println!("deep-link: Received {}", link);
+
+    let settings = crate::settings::get();
+    let result = parse(link, &settings.allowed_sites).and_then(|link| {
+        // For sites other than YouTube this also has yt-dlp confirm it has an extractor
+        let parsed = crate::parse_supported_url(app, &link.url)?;
+        Ok(DownloadLink { url: parsed.canonical_url, ..link })
+    });
+
+    let link = match result {
+        Ok(link) => link,
+        Err(e) => {
+            eprintln!("deep-link: Rejected {}: {}", link, e);
+            let _ = app.emit("deep-link-error", serde_json::json!({
+                "link": link.as_str(),
+                "error": e,
+            }));
+            return;
+        }
+    };
+
+    focus_main_window(app);
+    let message = format!(
+        "Another app asked Verdl to download:\n\n{}\n\nType: {}\nQuality: {}",
+        link.url,
+        link.download_type.as_deref().unwrap_or(&settings.default_download_type),
+        link.video_quality.as_deref().unwrap_or(&settings.default_video_quality),
+    );
+
+    let mut dialog = app.dialog()
+        .message(message)
+        .title("Download link")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom("Download".to_string(), "Ignore".to_string()));
+    if let Some(window) = app.get_webview_window("main") {
+        dialog = dialog.parent(&window);
+    }
+
+    let app = app.clone();
+    dialog.show(move |accepted| {
+        if !accepted {
+            println!("deep-link: Ignored {}", link.url);
+            return;
+        }
+
+        let _ = app.emit("deep-link-download", serde_json::json!({ "url": link.url }));
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::queue_url(app.clone(), link.url.clone(), link.download_type, link.video_quality).await {
+                let _ = app.emit("download-error", serde_json::json!({
+                    "url": link.url,
+                    "category": crate::errors::classify(&e),
+                    "error": e,
+                }));
+            }
+        });
+    });
+}
+
+/// Bring the main window to the front, e.g. when a second instance was started
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Register the scheme with the OS and start handling links, including the one we were launched with
+pub fn setup(app: &AppHandle) {
+    // Installed bundles register the scheme themselves (the .desktop MimeType entry on Linux),
+    // this covers AppImages and development builds
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("deep-link: Failed to register {}:// handler: {}", SCHEME, e);
+    }
+
+    let app_handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for link in event.urls() {
+            handle(&app_handle, &link);
+        }
+    });
+
+    if let Ok(Some(links)) = app.deep_link().get_current() {
+        for link in links {
+            handle(app, &link);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(url: &str, download_type: Option<&str>, quality: Option<&str>) -> DownloadLink {
+        DownloadLink {
+            url: url.to_string(),
+            download_type: download_type.map(String::from),
+            video_quality: quality.map(String::from),
+        }
+    }
+
+    #[test]
+    fn parses_download_links() {
+        let allowed = vec!["youtube.com".to_string(), "youtu.be".to_string(), "vimeo.com".to_string()];
+        let cases = [
+            ("verdl://download?url=https%3A%2F%2Fyoutu.be%2FdQw4w9WgXcQ",
+                Ok(link("https://www.youtube.com/watch?v=dQw4w9WgXcQ", None, None))),
+            ("verdl:download?url=https://vimeo.com/76979871&type=audio",
+                Ok(link("https://vimeo.com/76979871", Some("audio"), None))),
+            ("verdl://download?url=https%3A%2F%2Fvimeo.com%2F76979871&quality=720p&type=video",
+                Ok(link("https://vimeo.com/76979871", Some("video"), Some("720p")))),
+            // Unknown parameters are ignored
+            ("verdl://download?url=https%3A%2F%2Fvimeo.com%2F76979871&source=extension&quality=best",
+                Ok(link("https://vimeo.com/76979871", None, Some("best")))),
+            // Wrong scheme or action
+            ("https://download?url=https%3A%2F%2Fvimeo.com%2F76979871", Err(())),
+            ("verdl://open?url=https%3A%2F%2Fvimeo.com%2F76979871", Err(())),
+            ("verdl://?url=https%3A%2F%2Fvimeo.com%2F76979871", Err(())),
+            // Missing, empty or broken url
+            ("verdl://download", Err(())),
+            ("verdl://download?url=", Err(())),
+            ("verdl://download?url=%20%20", Err(())),
+            ("verdl://download?url=vimeo.com%2F76979871", Err(())),
+            ("verdl://download?url=https%3A%2F%2Fvimeo.com%2F7697%22%3B%20rm%20-rf", Err(())),
+            // Sites outside the allowlist
+            ("verdl://download?url=https%3A%2F%2Fexample.com%2Fvideo.mp4", Err(())),
+            ("verdl://download?url=https%3A%2F%2Fvimeo.com.evil.example%2F1", Err(())),
+            // Invalid options
+            ("verdl://download?url=https%3A%2F%2Fvimeo.com%2F76979871&quality=8k", Err(())),
+            ("verdl://download?url=https%3A%2F%2Fvimeo.com%2F76979871&type=playlist", Err(())),
+        ];
+
+        for (input, expected) in cases {
+            let url = Url::parse(input).unwrap();
+            assert_eq!(parse(&url, &allowed).map_err(|_| ()), expected, "link {}", input);
+        }
+    }
+}
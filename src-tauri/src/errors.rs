@@ -0,0 +1,148 @@
+// Error categories
+// Errors travel as plain strings (mostly yt-dlp's stderr), this sorts them into
+// categories the UI, the API and the CLI's exit codes can act on
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCategory {
+    /// Bad URL, options or time ranges; retrying won't help
+    InvalidInput,
+    /// yt-dlp is missing and couldn't be installed or started
+    YtdlpMissing,
+    /// YouTube wants a sign-in to prove we're not a bot, or is rate limiting
+    BotCheck,
+    /// yt-dlp couldn't make sense of the site any more, usually fixed by updating it
+    ExtractorBroken,
+    /// Private, removed, region-locked, age-restricted or not yet live
+    Unavailable,
+    /// Timeouts, DNS and connection failures
+    Network,
+    /// Output folder can't be created or written
+    Filesystem,
+    Cancelled,
+    Other,
+}
+
+impl ErrorCategory {
+    /// Process exit code used by the CLI
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCategory::Other => 1,
+            ErrorCategory::InvalidInput => 2,
+            ErrorCategory::Network => 3,
+            ErrorCategory::BotCheck => 4,
+            ErrorCategory::Unavailable => 5,
+            ErrorCategory::ExtractorBroken => 6,
+            ErrorCategory::YtdlpMissing => 7,
+            ErrorCategory::Filesystem => 8,
+            ErrorCategory::Cancelled => 130, // Same as being stopped with Ctrl+C
+        }
+    }
+}
+
+// Checked in order, the first category with a matching phrase wins
+// Age checks come before the bot check since both start with "sign in to confirm"
+// yt-dlp adds "please report this issue" to any unexpected error, so that alone says nothing
+const PATTERNS: &[(ErrorCategory, &[&str])] = &[
+    (ErrorCategory::Cancelled, &["cancelled"]),
+    (ErrorCategory::InvalidInput, &[
+        "invalid command", "invalid option", "invalid arguments",
+        "invalid url", "invalid live mode", "invalid range", "invalid timestamp", "invalid download type",
+        "outside the video duration", "can't be combined", "has no extractor for", "unsupported url",
+    ]),
+    (ErrorCategory::YtdlpMissing, &[
+        "failed to download yt-dlp", "failed to verify yt-dlp download", "failed to execute yt-dlp",
+        "failed to spawn download process",
+    ]),
+    (ErrorCategory::Unavailable, &[
+        "sign in to confirm your age", "age-restricted", "video unavailable", "private video",
+        "has been removed", "members-only", "not available in your country", "geo restriction",
+        "premieres in", "this live event will begin",
+    ]),
+    (ErrorCategory::BotCheck, &["not a bot", "sign in to confirm", "http error 429", "too many requests"]),
+    (ErrorCategory::ExtractorBroken, &[
+        "unable to extract", "signature extraction failed", "nsig extraction failed",
+        "an extractor error has occurred",
+    ]),
+    (ErrorCategory::Network, &[
+        "timed out", "connection reset", "connection refused", "failed to resolve", "name resolution",
+        "network is unreachable", "unable to download webpage", "http error 5",
+    ]),
+    (ErrorCategory::Filesystem, &[
+        "failed to create output directory", "no space left", "permission denied", "read-only file system",
+        "path is too long", "path contains null bytes",
+    ]),
+];
+
+/// Category of an error message
+pub fn classify(message: &str) -> ErrorCategory {
+    let message = message.to_lowercase();
+    PATTERNS.iter()
+        .find(|(_, phrases)| phrases.iter().any(|phrase| message.contains(phrase)))
+        .map(|(category, _)| *category)
+        .unwrap_or(ErrorCategory::Other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = "; please report this issue on  https://github.com/yt-dlp/yt-dlp/issues?q= , \
+        filling out the appropriate issue template. Confirm you are on the latest version using  yt-dlp -U";
+
+    #[test]
+    fn classifies_ytdlp_errors() {
+        let cases = [
+            ("ERROR: [youtube] dQw4w9WgXcQ: Sign in to confirm you\u{2019}re not a bot. Use --cookies-from-browser or --cookies for the authentication.",
+                ErrorCategory::BotCheck),
+            ("ERROR: unable to download video data: HTTP Error 429: Too Many Requests", ErrorCategory::BotCheck),
+            ("ERROR: [youtube] dQw4w9WgXcQ: Sign in to confirm your age. This video may be inappropriate for some users.",
+                ErrorCategory::Unavailable),
+            ("ERROR: [youtube] dQw4w9WgXcQ: Video unavailable. This video has been removed by the uploader", ErrorCategory::Unavailable),
+            ("ERROR: [youtube] dQw4w9WgXcQ: Private video. Sign in if you've been granted access to this video", ErrorCategory::Unavailable),
+            ("ERROR: [youtube] dQw4w9WgXcQ: This live event will begin in 3 hours.", ErrorCategory::Unavailable),
+            (&*format!("ERROR: [youtube] dQw4w9WgXcQ: Unable to extract uploader id{}", REPORT), ErrorCategory::ExtractorBroken),
+            (&*format!("ERROR: [youtube] dQw4w9WgXcQ: An extractor error has occurred. (caused by KeyError('videoDetails')){}", REPORT),
+                ErrorCategory::ExtractorBroken),
+            ("WARNING: [youtube] dQw4w9WgXcQ: nsig extraction failed: Some formats may be missing", ErrorCategory::ExtractorBroken),
+            // The bug report hint alone isn't a broken extractor, and mustn't trigger a yt-dlp update
+            (&*format!("ERROR: 'NoneType' object has no attribute 'get'{}", REPORT), ErrorCategory::Other),
+            ("ERROR: [generic] Unable to download webpage: <urlopen error [Errno -3] Temporary failure in name resolution>",
+                ErrorCategory::Network),
+            ("ERROR: unable to download video data: <urlopen error timed out>", ErrorCategory::Network),
+            ("ERROR: unable to download video data: HTTP Error 503: Service Unavailable", ErrorCategory::Network),
+            ("ERROR: unable to open for writing: [Errno 13] Permission denied: '/srv/videos/clip.mp4.part'", ErrorCategory::Filesystem),
+            ("ERROR: unable to write data: [Errno 28] No space left on device", ErrorCategory::Filesystem),
+            ("ERROR: Unsupported URL: https://example.com/", ErrorCategory::InvalidInput),
+            ("Failed to execute yt-dlp: No such file or directory (os error 2)", ErrorCategory::YtdlpMissing),
+            ("Download cancelled", ErrorCategory::Cancelled),
+            ("ERROR: Postprocessing: ffprobe and ffmpeg not found. Please install or provide the path using --ffmpeg-location",
+                ErrorCategory::Other),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(classify(message), expected, "category of {:?}", message);
+        }
+    }
+
+    #[test]
+    fn exit_codes() {
+        let cases = [
+            (ErrorCategory::Other, 1),
+            (ErrorCategory::InvalidInput, 2),
+            (ErrorCategory::Network, 3),
+            (ErrorCategory::BotCheck, 4),
+            (ErrorCategory::Unavailable, 5),
+            (ErrorCategory::ExtractorBroken, 6),
+            (ErrorCategory::YtdlpMissing, 7),
+            (ErrorCategory::Filesystem, 8),
+            (ErrorCategory::Cancelled, 130),
+        ];
+
+        for (category, code) in cases {
+            assert_eq!(category.exit_code(), code, "exit code of {:?}", category);
+        }
+    }
+}
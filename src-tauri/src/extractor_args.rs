@@ -0,0 +1,151 @@
+// YouTube extractor arguments from settings: player clients and proof-of-origin (PO) tokens
+// Passed to metadata and download calls alike, so both see the same formats
+
+use crate::settings::Settings;
+
+/// Player client name as yt-dlp knows it (web, mweb, tv_simply, ...), "-name" excludes one
+pub fn is_valid_player_client(client: &str) -> bool {
+    let name = client.strip_prefix('-').unwrap_or(client);
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// PO token as CLIENT.CONTEXT+TOKEN, e.g. web.gvs+MnQ...
+pub fn is_valid_po_token(token: &str) -> bool {
+    let Some((target, value)) = token.split_once('+') else {
+        return false;
+    };
+    let Some((client, context)) = target.split_once('.') else {
+        return false;
+    };
+    is_valid_player_client(client)
+        && !context.is_empty() && context.chars().all(|c| c.is_ascii_alphanumeric())
+        && !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_=%".contains(c))
+}
+
+/// PO token provider base URL; `;` and `,` would split it into further extractor arguments
+pub fn is_valid_provider_url(url: &str) -> bool {
+    (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.chars().any(|c| c == ';' || c == ',' || c.is_whitespace() || c.is_control())
+}
+
+/// --extractor-args for the configured player clients, PO tokens and PO token provider
+pub fn args(settings: &Settings) -> Vec<String> {
+    let mut youtube = Vec::new();
+    if !settings.youtube_player_clients.is_empty() {
+        youtube.push(format!("player_client={}", settings.youtube_player_clients.join(",")));
+    }
+    if !settings.youtube_po_tokens.is_empty() {
+        youtube.push(format!("po_token={}", settings.youtube_po_tokens.join(",")));
+    }
+
+    let mut args = Vec::new();
+    if !youtube.is_empty() {
+        args.push("--extractor-args".to_string());
+        args.push(format!("youtube:{}", youtube.join(";")));
+    }
+
+    // Tokens on demand from a bgutil-style HTTP server (needs the provider's yt-dlp plugin)
+    if let Some(url) = settings.po_token_provider_url.as_deref().filter(|url| !url.is_empty()) {
+        args.push("--extractor-args".to_string());
+        args.push(format!("youtubepot-bgutilhttp:base_url={}", url.trim_end_matches('/')));
+    }
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_player_clients() {
+        let cases = [
+            ("web", true),
+            ("tv_simply", true),
+            ("-web_safari", true),
+            ("mweb2", true),
+            ("", false),
+            ("-", false),
+            ("web,mweb", false),
+            ("web;rm", false),
+            ("web mweb", false),
+            ("web\t", false),
+            ("--web", false),
+            ("web.gvs", false),
+        ];
+
+        for (client, expected) in cases {
+            assert_eq!(is_valid_player_client(client), expected, "player client {:?}", client);
+        }
+    }
+
+    #[test]
+    fn validates_po_tokens() {
+        let cases = [
+            ("web.gvs+MnQxYz-_=%3D", true),
+            ("mweb.player+abc123", true),
+            ("tv_simply.subs+TOKEN", true),
+            ("web.gvs", false),
+            ("web+abc", false),
+            ("web.+abc", false),
+            ("web.gvs+", false),
+            (".gvs+abc", false),
+            ("web.gvs+abc,mweb.gvs+def", false),
+            ("web.gvs+abc;player_client=tv", false),
+            ("web.gvs+abc def", false),
+            ("web.gvs+abc\n", false),
+            ("web.g-vs+abc", false),
+        ];
+
+        for (token, expected) in cases {
+            assert_eq!(is_valid_po_token(token), expected, "PO token {:?}", token);
+        }
+    }
+
+    #[test]
+    fn validates_provider_urls() {
+        let cases = [
+            ("http://127.0.0.1:4416", true),
+            ("https://pot.example.com/base/", true),
+            ("127.0.0.1:4416", false),
+            ("ftp://pot.example.com", false),
+            ("http://127.0.0.1:4416;player_client=tv", false),
+            ("http://127.0.0.1:4416,other", false),
+            ("http://127.0.0.1:4416 x", false),
+            ("http://127.0.0.1:4416\n", false),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(is_valid_provider_url(url), expected, "provider {:?}", url);
+        }
+    }
+
+    fn settings(clients: &[&str], tokens: &[&str], provider: Option<&str>) -> Settings {
+        Settings {
+            youtube_player_clients: clients.iter().map(|c| c.to_string()).collect(),
+            youtube_po_tokens: tokens.iter().map(|t| t.to_string()).collect(),
+            po_token_provider_url: provider.map(String::from),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn builds_extractor_args() {
+        let cases: [(Settings, &[&str]); 6] = [
+            (settings(&[], &[], None), &[]),
+            (settings(&[], &[], Some("")), &[]),
+            (settings(&["tv_simply", "-web"], &[], None),
+                &["--extractor-args", "youtube:player_client=tv_simply,-web"]),
+            (settings(&[], &["web.gvs+abc", "mweb.gvs+def"], None),
+                &["--extractor-args", "youtube:po_token=web.gvs+abc,mweb.gvs+def"]),
+            (settings(&["mweb"], &["mweb.gvs+abc"], None),
+                &["--extractor-args", "youtube:player_client=mweb;po_token=mweb.gvs+abc"]),
+            (settings(&[], &[], Some("http://127.0.0.1:4416/")),
+                &["--extractor-args", "youtubepot-bgutilhttp:base_url=http://127.0.0.1:4416"]),
+        ];
+
+        for (settings, expected) in cases {
+            assert_eq!(args(&settings), expected, "args for {:?}", settings);
+        }
+    }
+}
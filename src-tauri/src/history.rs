@@ -0,0 +1,151 @@
+// Download queue and history, built from the `download-progress` events
+// Every download path already reports through that event, so tracking it here
+// keeps the monitor code untouched; finished downloads are kept in history.json
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Listener, Manager};
+
+const HISTORY_FILE: &str = "history.json";
+
+/// Finished downloads kept at most, the oldest are dropped
+const MAX_HISTORY: usize = 500;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadEntry {
+    pub id: String,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub download_type: Option<String>,
+    pub status: String,
+    pub progress: f64,
+    pub live: bool,
+    pub error: Option<String>,
+    pub started_at: u64, // Unix seconds
+    pub finished_at: Option<u64>,
+}
+
+#[derive(Default)]
+struct Downloads {
+    active: HashMap<String, DownloadEntry>,
+    history: Vec<DownloadEntry>, // Oldest first
+    path: Option<PathBuf>,
+}
+
+static DOWNLOADS: Lazy<Mutex<Downloads>> = Lazy::new(|| Mutex::new(Downloads::default()));
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Load the saved history and start following download events
+pub fn init(app: &AppHandle) {
+    let path = app.path().app_data_dir().ok().map(|dir| dir.join(HISTORY_FILE));
+
+    let history = path.as_ref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str::<Vec<DownloadEntry>>(&contents).ok())
+        .unwrap_or_default();
+
+    if let Ok(mut downloads) = DOWNLOADS.lock() {
+        downloads.history = history;
+        downloads.path = path;
+    }
+
+    app.listen_any("download-progress", |event| {
+        if let Ok(payload) = serde_json::from_str::<Value>(event.payload()) {
+            record(&payload);
+        }
+    });
+}
+
+/// Merge a `download-progress` payload into its entry, moving it to history once finished
+fn record(payload: &Value) {
+    let Some(id) = payload["id"].as_str() else {
+        return;
+    };
+    let status = payload["status"].as_str().unwrap_or_default();
+
+    let Ok(mut downloads) = DOWNLOADS.lock() else {
+        return;
+    };
+
+    let entry = downloads.active.entry(id.to_string()).or_insert_with(|| DownloadEntry {
+        id: id.to_string(),
+        started_at: now(),
+        ..Default::default()
+    });
+
+    // Progress events only carry what changed
+    let text = |key: &str| payload[key].as_str().map(|s| s.to_string());
+    entry.url = text("url").or(entry.url.take());
+    entry.title = text("title").or(entry.title.take());
+    entry.download_type = text("downloadType").or(entry.download_type.take());
+    entry.error = text("error").or(entry.error.take());
+    entry.progress = payload["progress"].as_f64().unwrap_or(entry.progress);
+    entry.live |= payload["live"].as_bool().unwrap_or(false);
+    if !status.is_empty() {
+        entry.status = status.to_string();
+    }
+
+    if !["completed", "error", "cancelled"].contains(&status) {
+        return;
+    }
+
+    if let Some(mut entry) = downloads.active.remove(id) {
+        entry.finished_at = Some(now());
+        downloads.history.push(entry);
+        let excess = downloads.history.len().saturating_sub(MAX_HISTORY);
+        downloads.history.drain(..excess);
+        save(&downloads);
+    }
+}
+
+fn save(downloads: &Downloads) {
+    let Some(path) = &downloads.path else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    match serde_json::to_string(&downloads.history) {
+        Ok(contents) => {
+            if let Err(e) = fs::write(path, contents) {
+                eprintln!("Failed to write download history: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to serialize download history: {}", e),
+    }
+}
+
+/// Downloads that are still running, oldest first
+pub fn active() -> Vec<DownloadEntry> {
+    let mut active: Vec<DownloadEntry> = DOWNLOADS.lock()
+        .map(|downloads| downloads.active.values().cloned().collect())
+        .unwrap_or_default();
+    active.sort_by_key(|entry| entry.started_at);
+    active
+}
+
+/// Finished downloads, newest first
+pub fn history() -> Vec<DownloadEntry> {
+    DOWNLOADS.lock()
+        .map(|downloads| downloads.history.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Forget all finished downloads
+pub fn clear() {
+    if let Ok(mut downloads) = DOWNLOADS.lock() {
+        downloads.history.clear();
+        save(&downloads);
+    }
+}
@@ -0,0 +1,42 @@
+// What the download logic needs from the environment it runs in:
+// somewhere to report events and the app's directories
+// Implemented by the Tauri app and by the headless CLI
+
+use std::path::PathBuf;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub trait Host: Clone + Send + Sync + 'static {
+    /// Report an event (`download-progress`, `metadata-entry`, ...) to the UI or terminal
+    fn emit_event(&self, event: &str, payload: Value);
+
+    /// Directory with files bundled alongside the app
+    fn resource_dir(&self) -> Result<PathBuf, String>;
+
+    /// Per-user data directory, where the managed yt-dlp lives
+    fn local_data_dir(&self) -> Result<PathBuf, String>;
+
+    /// Per-user config directory, where settings.json lives
+    fn config_dir(&self) -> Result<PathBuf, String>;
+}
+
+impl Host for AppHandle {
+    fn emit_event(&self, event: &str, payload: Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn resource_dir(&self) -> Result<PathBuf, String> {
+        self.path().resource_dir()
+            .map_err(|e| format!("Failed to get resource dir: {}", e))
+    }
+
+    fn local_data_dir(&self) -> Result<PathBuf, String> {
+        self.path().app_local_data_dir()
+            .map_err(|e| format!("Failed to get local data dir: {}", e))
+    }
+
+    fn config_dir(&self) -> Result<PathBuf, String> {
+        self.path().app_config_dir()
+            .map_err(|e| format!("Failed to get config dir: {}", e))
+    }
+}
@@ -0,0 +1,229 @@
+// Batch URL import from pasted text or text files
+// Pulls every supported URL out of free-form text (chat messages, notes, URL lists),
+// deduplicates them by video/playlist id and reports lines that yielded nothing
+
+use std::collections::HashSet;
+use std::path::Path;
+use regex::Regex;
+use serde::Serialize;
+use crate::urls::ParsedUrl;
+
+/// Text files larger than this are refused
+const MAX_IMPORT_BYTES: u64 = 10 * 1024 * 1024;
+
+/// URL candidates: anything starting with a scheme, or a bare www. or allowed host
+/// (subdomains included) followed by a path, as typed in chats
+fn url_candidate_pattern(allowed_sites: &[String]) -> Regex {
+    let hosts: String = allowed_sites.iter()
+        .map(|site| format!(r"|\b(?:[a-z0-9-]+\.)*{}/", regex::escape(site)))
+        .collect();
+    Regex::new(&format!(r#"(?i)(?:https?://|\bwww\.{})[^\s<>"'`]+"#, hosts))
+        .expect("valid URL candidate pattern")
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedUrl {
+    pub line: usize, // 1-based line the URL was found on
+    #[serde(flatten)]
+    pub url: ParsedUrl,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnrecognizedLine {
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportResult {
+    pub urls: Vec<ImportedUrl>,
+    pub duplicates: usize,
+    pub unrecognized: Vec<UnrecognizedLine>,
+}
+
+/// Read a text file for import, refusing anything unreasonably large
+pub fn read_file(path: &Path) -> Result<String, String> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if metadata.len() > MAX_IMPORT_BYTES {
+        return Err(format!("File is too large to import (maximum {} MB)", MAX_IMPORT_BYTES / (1024 * 1024)));
+    }
+
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Extract, validate and deduplicate every URL in `text`
+/// Bare hosts are recognized for `allowed_sites`; `parse` decides what counts as supported
+/// (site allowlist, extractor check)
+pub fn extract_urls(text: &str, allowed_sites: &[String], parse: impl Fn(&str) -> Option<ParsedUrl>) -> ImportResult {
+    let url_candidate = url_candidate_pattern(allowed_sites);
+    let mut result = ImportResult::default();
+    let mut seen = HashSet::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let mut recognized = false;
+        for candidate in url_candidate.find_iter(trimmed) {
+            // A bare host right after another scheme (ftp://www..., file://youtube.com/...)
+            // is part of that URL, not a web link
+            if trimmed[..candidate.start()].ends_with("://") {
+                continue;
+            }
+
+            let Some(parsed) = parse(&normalize_candidate(candidate.as_str())) else {
+                continue;
+            };
+
+            recognized = true;
+            if seen.insert(parsed.dedup_key()) {
+                result.urls.push(ImportedUrl { line: index + 1, url: parsed });
+            } else {
+                result.duplicates += 1;
+            }
+        }
+
+        if !recognized {
+            result.unrecognized.push(UnrecognizedLine {
+                line: index + 1,
+                text: trimmed.chars().take(200).collect(),
+            });
+        }
+    }
+
+    result
+}
+
+/// Strip punctuation that belongs to the surrounding sentence and add a missing scheme
+fn normalize_candidate(candidate: &str) -> String {
+    let mut url = candidate.trim_end_matches(['.', ',', ';', ':', '!', '?', '>', '*']);
+
+    // Only strip closing brackets that don't have an opening one in the URL, e.g. "(see https://...)"
+    for (open, close) in [('(', ')'), ('[', ']'), ('{', '}')] {
+        while url.ends_with(close) && url.matches(open).count() < url.matches(close).count() {
+            url = &url[..url.len() - 1];
+        }
+    }
+
+    let has_scheme = ["http://", "https://"].iter()
+        .any(|prefix| url.get(..prefix.len()).is_some_and(|s| s.eq_ignore_ascii_case(prefix)));
+
+    match url.split_once("://") {
+        Some((scheme, rest)) if has_scheme => format!("{}://{}", scheme.to_ascii_lowercase(), rest),
+        _ => format!("https://{}", url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::urls;
+
+    fn extract(text: &str) -> ImportResult {
+        let youtube: Vec<String> = urls::YOUTUBE_DOMAINS.iter().map(|d| d.to_string()).collect();
+        extract_urls(text, &youtube, urls::parse)
+    }
+
+    fn extract_allowed(text: &str, allowed_sites: &[&str]) -> ImportResult {
+        let allowed_sites: Vec<String> = allowed_sites.iter().map(|d| d.to_string()).collect();
+        extract_urls(text, &allowed_sites, |url| urls::parse_allowed(url, &allowed_sites))
+    }
+
+    fn canonical_urls(result: &ImportResult) -> Vec<&str> {
+        result.urls.iter().map(|imported| imported.url.canonical_url.as_str()).collect()
+    }
+
+    #[test]
+    fn strips_trailing_punctuation() {
+        let cases = [
+            ("https://youtu.be/dQw4w9WgXcQ.", "https://youtu.be/dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ,", "https://youtu.be/dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ?!", "https://youtu.be/dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ);", "https://youtu.be/dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ]", "https://youtu.be/dQw4w9WgXcQ"),
+            ("https://en.wikipedia.org/wiki/Rust_(programming_language)", "https://en.wikipedia.org/wiki/Rust_(programming_language)"),
+            ("https://en.wikipedia.org/wiki/Rust_(programming_language)).", "https://en.wikipedia.org/wiki/Rust_(programming_language)"),
+            ("https://youtu.be/dQw4w9WgXcQ**", "https://youtu.be/dQw4w9WgXcQ"),
+        ];
+
+        for (candidate, expected) in cases {
+            assert_eq!(normalize_candidate(candidate), expected, "normalized {:?}", candidate);
+        }
+    }
+
+    #[test]
+    fn adds_missing_scheme() {
+        assert_eq!(normalize_candidate("youtu.be/dQw4w9WgXcQ"), "https://youtu.be/dQw4w9WgXcQ");
+        assert_eq!(normalize_candidate("www.youtube.com/watch?v=dQw4w9WgXcQ"), "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(normalize_candidate("HTTPS://youtu.be/dQw4w9WgXcQ"), "https://youtu.be/dQw4w9WgXcQ");
+    }
+
+    #[test]
+    fn extracts_urls_from_text() {
+        let result = extract("Watch this (https://youtu.be/dQw4w9WgXcQ), and youtube.com/shorts/dQw4w9WgXcR.\n\n  www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf  ");
+        assert_eq!(canonical_urls(&result), [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://www.youtube.com/shorts/dQw4w9WgXcR",
+            "https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf",
+        ]);
+        assert_eq!(result.urls.iter().map(|imported| imported.line).collect::<Vec<_>>(), [1, 1, 3]);
+        assert!(result.unrecognized.is_empty());
+    }
+
+    #[test]
+    fn counts_duplicates() {
+        let result = extract(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ\nhttps://youtu.be/dQw4w9WgXcQ?t=42\nhttps://m.youtube.com/watch?v=dQw4w9WgXcQ https://youtu.be/dQw4w9WgXcQ",
+        );
+        assert_eq!(canonical_urls(&result), ["https://www.youtube.com/watch?v=dQw4w9WgXcQ"]);
+        assert_eq!(result.duplicates, 3);
+        assert!(result.unrecognized.is_empty());
+    }
+
+    #[test]
+    fn ignores_non_http_schemes() {
+        let result = extract("ftp://www.youtube.com/watch?v=dQw4w9WgXcQ\nfile://youtu.be/dQw4w9WgXcQ\njavascript:alert(1)\nmailto:someone@example.com");
+        assert!(result.urls.is_empty());
+        assert_eq!(result.unrecognized.iter().map(|line| line.line).collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn reports_unrecognized_lines() {
+        let result = extract("just some notes\nhttps://vimeo.com/123456\nhttps://youtu.be/dQw4w9WgXcQ");
+        assert_eq!(canonical_urls(&result), ["https://www.youtube.com/watch?v=dQw4w9WgXcQ"]);
+        assert_eq!(result.unrecognized.len(), 2);
+        assert_eq!(result.unrecognized[0].text, "just some notes");
+        assert_eq!(result.unrecognized[1].line, 2);
+    }
+
+    #[test]
+    fn recognizes_bare_hosts_of_allowed_sites() {
+        let result = extract_allowed(
+            "vimeo.com/76979871 and soundcloud.com/artist/track\nplayer.vimeo.com/video/76979871\nyoutu.be/dQw4w9WgXcQ\nexample.com/video.mp4",
+            &["vimeo.com", "soundcloud.com", "youtu.be"],
+        );
+        assert_eq!(canonical_urls(&result), [
+            "https://vimeo.com/76979871",
+            "https://soundcloud.com/artist/track",
+            "https://player.vimeo.com/video/76979871",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+        ]);
+        assert_eq!(result.unrecognized.iter().map(|line| line.line).collect::<Vec<_>>(), [4]);
+    }
+
+    #[test]
+    fn ignores_bare_hosts_of_other_sites() {
+        // Only the www. form is picked up for sites outside the allowlist, then rejected by parse
+        let result = extract_allowed("vimeo.com/76979871\nnotvimeo.com/1\nwww.vimeo.com/76979871", &["soundcloud.com"]);
+        assert!(result.urls.is_empty());
+        assert_eq!(result.unrecognized.len(), 3);
+
+        let result = extract_allowed("notvimeo.com/76979871", &["vimeo.com"]);
+        assert!(result.urls.is_empty());
+    }
+}
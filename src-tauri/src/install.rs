@@ -0,0 +1,447 @@
+// Installation of the managed yt-dlp binary
+// The download is streamed to a temp file next to the target, verified, synced to disk
+// and only then renamed over the target, so a failed or interrupted install never
+// leaves a half-written yt-dlp behind. Progress is reported as `yt-dlp-install-progress`
+// The managed Deno (js_runtime.rs) is downloaded and installed the same way
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use crate::host::Host;
+use crate::settings::Settings;
+use crate::{verify, ytdlp_asset_name, ytdlp_binary_name};
+
+/// yt-dlp release channels, as understood by `yt-dlp --update-to`
+pub const CHANNELS: [&str; 3] = ["stable", "nightly", "master"];
+
+/// Attempts before giving up, waiting 2s, 4s, ... in between
+const MAX_ATTEMPTS: u32 = 3;
+
+/// An attempt is abandoned when no data arrived for this long
+const STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Upper bound for a whole attempt, even on a slow but steady link
+const MAX_DOWNLOAD_SECS: u64 = 15 * 60;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// GitHub repository a release channel is published from
+fn channel_repo(channel: &str) -> &'static str {
+    match channel {
+        "nightly" => "yt-dlp/yt-dlp-nightly-builds",
+        "master" => "yt-dlp/yt-dlp-master-builds",
+        _ => "yt-dlp/yt-dlp",
+    }
+}
+
+/// Release tags are dates, nightly and master builds add a time: 2024.08.06 or 2024.08.06.232908
+pub fn is_valid_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    (3..=4).contains(&parts.len())
+        && parts.iter().all(|part| !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The pinned version, if any
+fn pinned_version(settings: &Settings) -> Option<&str> {
+    settings.ytdlp_version.as_deref().filter(|v| !v.is_empty())
+}
+
+/// The configured mirror, if any
+pub fn mirror(settings: &Settings) -> Option<&str> {
+    settings.download_mirror.as_deref()
+        .map(|mirror| mirror.trim_end_matches(['/', '\\']))
+        .filter(|mirror| !mirror.is_empty())
+}
+
+/// A mirror on disk rather than a server: plain paths and file:// URLs
+pub fn local_path(url: &str) -> Option<PathBuf> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return None;
+    }
+    let Some(path) = url.strip_prefix("file://") else {
+        return Some(PathBuf::from(url));
+    };
+
+    // file:///C:/mirror is the Windows path C:/mirror, not /C:/mirror
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':';
+    Some(PathBuf::from(if has_drive { &path[1..] } else { path }))
+}
+
+/// Where to download the binary for the configured channel and version
+/// A mirror is laid out like github.com: <owner>/<repo>/releases/download/<version>/<file>,
+/// with releases/latest/download/ for the latest release
+pub fn download_url(settings: &Settings) -> String {
+    let base = mirror(settings).unwrap_or("https://github.com");
+    let repo = channel_repo(&settings.ytdlp_channel);
+    match pinned_version(settings) {
+        Some(version) => format!("{}/{}/releases/download/{}/{}", base, repo, version, ytdlp_asset_name()),
+        None => format!("{}/{}/releases/latest/download/{}", base, repo, ytdlp_asset_name()),
+    }
+}
+
+/// Argument for `yt-dlp --update-to`: the channel, or channel@version when pinned
+pub fn update_target(settings: &Settings) -> String {
+    match pinned_version(settings) {
+        Some(version) => format!("{}@{}", settings.ytdlp_channel, version),
+        None => settings.ytdlp_channel.clone(),
+    }
+}
+
+fn feed_url(settings: &Settings) -> Option<&str> {
+    settings.ytdlp_update_feed_url.as_deref().filter(|url| !url.is_empty())
+}
+
+/// Whether there is anywhere to look up the latest release: the feed, or GitHub when
+/// no mirror is set (a mirror is used because GitHub can't be reached)
+pub fn can_check_latest(settings: &Settings) -> bool {
+    feed_url(settings).is_some() || mirror(settings).is_none()
+}
+
+/// Latest release of the configured channel, from the release feed if one is set
+pub fn latest_version(settings: &Settings) -> Result<String, String> {
+    match feed_url(settings) {
+        Some(feed_url) => feed_version(feed_url),
+        None if !can_check_latest(settings) => Err(
+            "Failed to check the latest yt-dlp version: a download mirror is set without an update feed".to_string()
+        ),
+        None => github_latest_version(&settings.ytdlp_channel),
+    }
+}
+
+/// Latest release of a channel, from where GitHub redirects /releases/latest to
+fn github_latest_version(channel: &str) -> Result<String, String> {
+    let url = format!("https://github.com/{}/releases/latest", channel_repo(channel));
+    let response = minreq::head(&url)
+        .with_timeout(15)
+        .with_max_redirects(0)
+        .send()
+        .map_err(|e| format!("Failed to check the latest yt-dlp version: {}", e))?;
+
+    response.headers.get("location")
+        .and_then(|location| location.rsplit('/').next())
+        .filter(|tag| is_valid_version(tag))
+        .map(String::from)
+        .ok_or_else(|| format!("Failed to check the latest yt-dlp version: unexpected response (HTTP {})", response.status_code))
+}
+
+/// Version from a release feed: GitHub's releases/latest API JSON ("tag_name"),
+/// or a plain text file holding just the version, as a mirror would serve it
+fn feed_version(feed_url: &str) -> Result<String, String> {
+    let response = minreq::get(feed_url)
+        .with_header("Accept", "application/vnd.github+json")
+        .with_header("User-Agent", "verdl")
+        .with_timeout(15)
+        .send()
+        .map_err(|e| format!("Failed to check the latest yt-dlp version: {}", e))?;
+
+    if response.status_code < 200 || response.status_code >= 300 {
+        return Err(format!("Failed to check the latest yt-dlp version: {} returned HTTP {}", feed_url, response.status_code));
+    }
+
+    let body = response.as_str()
+        .map_err(|e| format!("Failed to check the latest yt-dlp version: {}", e))?
+        .trim();
+    let version = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(json) => json["tag_name"].as_str().unwrap_or_default().to_string(),
+        Err(_) => body.to_string(),
+    };
+
+    if is_valid_version(&version) {
+        Ok(version)
+    } else {
+        Err(format!("Failed to check the latest yt-dlp version: {} has no release version", feed_url))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionReport {
+    pub installed: Option<String>,
+    pub latest: Option<String>,
+    pub channel: String,
+    pub pinned: Option<String>,
+    /// What an update would install differs from what is installed
+    pub update_available: bool,
+}
+
+/// Installed version compared with the pinned one or the channel's latest
+pub fn version_report(installed: Option<String>, settings: &Settings) -> VersionReport {
+    let pinned = pinned_version(settings).map(String::from);
+    let latest = match latest_version(settings) {
+        Ok(latest) => Some(latest),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    };
+
+    let target = pinned.as_ref().or(latest.as_ref());
+    let update_available = match (&installed, target) {
+        (Some(installed), Some(target)) => installed != target,
+        _ => false,
+    };
+
+    VersionReport { installed, latest, channel: settings.ytdlp_channel.clone(), pinned, update_available }
+}
+
+/// Download `download_url` and install it as yt-dlp in `target_dir`
+pub fn download_ytdlp(app: &impl Host, download_url: &str, target_dir: &Path) -> Result<(), String> {
+    println!("download_ytdlp: Installing {} to {:?}", download_url, target_dir);
+
+    fs::create_dir_all(target_dir)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+    remove_stale_temp_files(target_dir, ytdlp_binary_name());
+
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_ATTEMPTS {
+        match try_install(app, download_url, target_dir, attempt) {
+            Ok(path) => {
+                println!("download_ytdlp: Installed {:?}", path);
+                emit_progress(app, "installed", attempt, 0, None);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("download_ytdlp: Attempt {} of {} failed: {}", attempt, MAX_ATTEMPTS, e);
+                last_error = e;
+            }
+        }
+
+        if attempt < MAX_ATTEMPTS {
+            emit_progress(app, "retrying", attempt, 0, None);
+            std::thread::sleep(Duration::from_secs(2u64.pow(attempt)));
+        }
+    }
+
+    emit_progress(app, "failed", MAX_ATTEMPTS, 0, None);
+    Err(last_error)
+}
+
+fn emit_progress(app: &impl Host, status: &str, attempt: u32, downloaded: u64, total: Option<u64>) {
+    app.emit_event("yt-dlp-install-progress", serde_json::json!({
+        "status": status,
+        "attempt": attempt,
+        "maxAttempts": MAX_ATTEMPTS,
+        "downloaded": downloaded,
+        "total": total,
+        "progress": total.filter(|t| *t > 0).map(|t| downloaded as f64 * 100.0 / t as f64),
+    }));
+}
+
+/// Temp file for an install of `file_name` in progress, hidden and unique per attempt
+pub(crate) fn temp_path(target_dir: &Path, file_name: &str) -> PathBuf {
+    target_dir.join(format!(".{}.{}.part", file_name, uuid::Uuid::new_v4().simple()))
+}
+
+/// Leftovers of installs of `file_name` that were interrupted by a crash or power loss
+pub(crate) fn remove_stale_temp_files(target_dir: &Path, file_name: &str) {
+    let prefix = format!(".{}.", file_name);
+    let Ok(entries) = fs::read_dir(target_dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(&prefix) && name.ends_with(".part") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn try_install(app: &impl Host, download_url: &str, target_dir: &Path, attempt: u32) -> Result<PathBuf, String> {
+    let temp = temp_path(target_dir, ytdlp_binary_name());
+    let target = target_dir.join(ytdlp_binary_name());
+
+    let result = (|| {
+        let checksum = stream_to_file(download_url, &temp, "yt-dlp", |downloaded, total| {
+            emit_progress(app, "downloading", attempt, downloaded, total)
+        })?;
+
+        emit_progress(app, "verifying", attempt, 0, None);
+        verify::verify_checksum(download_url, &checksum)?;
+
+        install_executable(&temp, &target, "yt-dlp")?;
+        Ok(target)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Make a completely written temp file executable and move it over `target`
+pub(crate) fn install_executable(temp: &Path, target: &Path, name: &str) -> Result<(), String> {
+    // On Unix-like systems (macOS/Linux), make the binary executable
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(temp, fs::Permissions::from_mode(0o755)) // rwxr-xr-x
+            .map_err(|e| format!("Failed to set executable permissions: {}", e))?;
+    }
+
+    fs::rename(temp, target)
+        .map_err(|e| format!("Failed to install {}: {}", name, e))?;
+
+    // Make the rename itself durable
+    #[cfg(not(target_os = "windows"))]
+    if let Some(dir) = target.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Reader for a download of `name` and its size, if known
+pub(crate) fn open_download(download_url: &str, name: &str) -> Result<(Box<dyn Read + Send>, Option<u64>), String> {
+    if let Some(local) = local_path(download_url) {
+        let file = File::open(&local)
+            .map_err(|e| format!("Failed to download {}: couldn't open {}: {}", name, local.display(), e))?;
+        let len = file.metadata().ok().map(|m| m.len());
+        return Ok((Box::new(file), len));
+    }
+
+    let response = minreq::get(download_url)
+        .with_timeout(MAX_DOWNLOAD_SECS)
+        .send_lazy()
+        .map_err(|e| format!("Failed to download {}: {}", name, e))?;
+
+    println!("install: {} returned status code {}", download_url, response.status_code);
+    if response.status_code < 200 || response.status_code >= 300 {
+        return Err(format!("Failed to download {}: HTTP {}", name, response.status_code));
+    }
+
+    let total = response.headers.get("content-length").and_then(|len| len.parse::<u64>().ok());
+    Ok((Box::new(response), total))
+}
+
+/// Stream the download of `name` into `path`, returning the SHA-256 of what was written
+/// `progress` is called with the bytes received so far and the total, if known
+pub(crate) fn stream_to_file(
+    download_url: &str,
+    path: &Path,
+    name: &str,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<String, String> {
+    let (source, total) = open_download(download_url, name)?;
+
+    // Read on a separate thread so a stalled connection can be given up on
+    let (sender, receiver) = mpsc::sync_channel::<std::io::Result<Vec<u8>>>(16);
+    std::thread::spawn(move || {
+        let mut source = source;
+        loop {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            let result = source.read(&mut chunk).map(|n| {
+                chunk.truncate(n);
+                chunk
+            });
+            let done = !matches!(&result, Ok(chunk) if !chunk.is_empty());
+            if sender.send(result).is_err() || done {
+                break;
+            }
+        }
+    });
+
+    let mut file = File::create(path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0u64;
+    let mut last_report = Instant::now();
+    progress(0, total);
+
+    loop {
+        let chunk = match receiver.recv_timeout(STALL_TIMEOUT) {
+            Ok(Ok(chunk)) if chunk.is_empty() => break,
+            Ok(Ok(chunk)) => chunk,
+            Ok(Err(e)) => return Err(format!("Failed to download {}: {}", name, e)),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                return Err(format!("Failed to download {}: no data received for {}s", name, STALL_TIMEOUT.as_secs()));
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(format!("Failed to download {}: connection closed", name));
+            }
+        };
+
+        file.write_all(&chunk)
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        hasher.update(&chunk);
+        downloaded += chunk.len() as u64;
+
+        if last_report.elapsed() >= Duration::from_millis(250) {
+            progress(downloaded, total);
+            last_report = Instant::now();
+        }
+    }
+
+    if let Some(total) = total.filter(|total| *total != downloaded) {
+        return Err(format!("Failed to download {}: got {} of {} bytes", name, downloaded, total));
+    }
+    progress(downloaded, total);
+
+    // Everything has to be on disk before the file can replace the installed one
+    file.sync_all()
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    println!("install: Downloaded {} bytes of {}", downloaded, name);
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_mirror(mirror: Option<&str>) -> Settings {
+        Settings { download_mirror: mirror.map(String::from), ..Settings::default() }
+    }
+
+    #[test]
+    fn trims_mirror() {
+        let cases = [
+            (None, None),
+            (Some(""), None),
+            (Some("/"), None),
+            (Some("https://mirror.example.com"), Some("https://mirror.example.com")),
+            (Some("https://mirror.example.com/"), Some("https://mirror.example.com")),
+            (Some("/srv/mirror//"), Some("/srv/mirror")),
+            (Some("C:\\mirror\\"), Some("C:\\mirror")),
+        ];
+
+        for (configured, expected) in cases {
+            assert_eq!(mirror(&with_mirror(configured)), expected, "mirror for {:?}", configured);
+        }
+    }
+
+    #[test]
+    fn resolves_local_paths() {
+        let cases = [
+            ("https://mirror.example.com/yt-dlp", None),
+            ("http://mirror.example.com/yt-dlp", None),
+            ("/srv/mirror/yt-dlp", Some("/srv/mirror/yt-dlp")),
+            ("file:///srv/mirror/yt-dlp", Some("/srv/mirror/yt-dlp")),
+            ("file:///C:/mirror/yt-dlp.exe", Some("C:/mirror/yt-dlp.exe")),
+            ("file://C:/mirror/yt-dlp.exe", Some("C:/mirror/yt-dlp.exe")),
+            ("C:\\mirror\\yt-dlp.exe", Some("C:\\mirror\\yt-dlp.exe")),
+            ("\\\\server\\share\\yt-dlp.exe", Some("\\\\server\\share\\yt-dlp.exe")),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(local_path(url), expected.map(PathBuf::from), "local path for {}", url);
+        }
+    }
+
+    #[test]
+    fn mirror_without_feed_skips_github() {
+        assert!(can_check_latest(&with_mirror(None)));
+        assert!(!can_check_latest(&with_mirror(Some("https://mirror.example.com"))));
+        assert!(latest_version(&with_mirror(Some("/srv/mirror"))).is_err());
+
+        let with_feed = Settings {
+            ytdlp_update_feed_url: Some("https://mirror.example.com/latest".to_string()),
+            ..with_mirror(Some("https://mirror.example.com"))
+        };
+        assert!(can_check_latest(&with_feed));
+    }
+}
@@ -0,0 +1,353 @@
+// JavaScript runtime for yt-dlp's YouTube challenge solving
+// yt-dlp 2025.11.12 and later need Deno, Node or Bun to solve YouTube's player challenges,
+// without one formats go missing or downloads fail. An installed runtime is detected on PATH;
+// optionally Verdl downloads and manages its own Deno, the way it manages yt-dlp.
+// That download only ever runs in the background (install_in_background), never while a
+// yt-dlp command is being put together
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+use crate::host::Host;
+use crate::{find_on_path, install, settings};
+
+/// Runtimes yt-dlp supports, in the order they are looked for
+pub const RUNTIMES: [&str; 3] = ["deno", "node", "bun"];
+
+/// First yt-dlp release with --js-runtimes
+const MIN_YTDLP_VERSION: &str = "2025.11.12";
+
+/// The runtime in use: (name, path); Some(None) when none was found
+static RUNTIME: Mutex<Option<Option<(String, PathBuf)>>> = Mutex::new(None);
+
+/// Whether the yt-dlp at the path understands --js-runtimes, checked once per yt-dlp
+static YTDLP_SUPPORT: Mutex<Option<(PathBuf, bool)>> = Mutex::new(None);
+
+/// Held while Deno is being installed, so only one install runs at a time
+static INSTALL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Install attempts before giving up until the next start or settings change
+const MAX_ATTEMPTS: u32 = 3;
+
+fn executable(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Deno release asset for this platform
+fn deno_asset_name() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("windows", "x86_64") => Some("deno-x86_64-pc-windows-msvc.zip"),
+        ("macos", "x86_64") => Some("deno-x86_64-apple-darwin.zip"),
+        ("macos", "aarch64") => Some("deno-aarch64-apple-darwin.zip"),
+        ("linux", "x86_64") => Some("deno-x86_64-unknown-linux-gnu.zip"),
+        ("linux", "aarch64") => Some("deno-aarch64-unknown-linux-gnu.zip"),
+        _ => None,
+    }
+}
+
+fn managed_deno(app: &impl Host) -> Result<PathBuf, String> {
+    Ok(app.local_data_dir()?.join(executable("deno")))
+}
+
+/// What check_ytdlp_installed reports about the JS runtime
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeStatus {
+    pub name: Option<String>,
+    pub path: Option<String>,
+    /// Whether the yt-dlp in use can be handed the runtime (--js-runtimes)
+    pub supported: bool,
+}
+
+/// Runtimes the `js_runtime` setting allows, in the order they are looked for
+fn wanted_runtimes(js_runtime: &str) -> Vec<&str> {
+    match js_runtime {
+        "none" => Vec::new(),
+        "auto" => RUNTIMES.to_vec(),
+        name => vec![name],
+    }
+}
+
+/// Find the runtime according to the `js_runtime` setting: on PATH, or the managed Deno
+fn resolve(app: &impl Host) -> Option<(String, PathBuf)> {
+    if let Ok(cached) = RUNTIME.lock() {
+        if let Some(runtime) = cached.as_ref() {
+            return runtime.clone();
+        }
+    }
+
+    let settings = settings::get();
+    let wanted = wanted_runtimes(&settings.js_runtime);
+
+    let mut runtime = wanted.iter()
+        .find_map(|name| find_on_path(&executable(name)).map(|path| (name.to_string(), path)));
+
+    if runtime.is_none() && wanted.contains(&"deno") {
+        runtime = managed_deno(app).ok()
+            .filter(|path| path.is_file())
+            .map(|path| ("deno".to_string(), path));
+    }
+
+    match &runtime {
+        Some((name, path)) => println!("js_runtime: Using {} at {:?}", name, path),
+        None if !wanted.is_empty() => eprintln!("js_runtime: No JavaScript runtime found, YouTube downloads may fail"),
+        None => {}
+    }
+
+    if let Ok(mut cached) = RUNTIME.lock() {
+        *cached = Some(runtime.clone());
+    }
+    runtime
+}
+
+/// Download Deno on a background thread when allowed and no runtime was found
+pub fn install_in_background(app: impl Host) {
+    std::thread::spawn(move || install(&app));
+}
+
+/// Download Deno when allowed and no runtime was found, blocking until done
+/// Progress is reported as `js-runtime-install-progress` events. A failed install isn't
+/// remembered: the next call (next start, settings change) tries again
+pub fn install(app: &impl Host) {
+    let settings = settings::get();
+    if !settings.js_runtime_download || !wanted_runtimes(&settings.js_runtime).contains(&"deno") {
+        return;
+    }
+
+    // Wait for an install that is already running rather than starting a second one
+    let _guard = INSTALL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if resolve(app).is_some() {
+        return;
+    }
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match download_deno(app) {
+            Ok(_) => {
+                // Look again, so the new Deno is picked up by the next yt-dlp command
+                if let Ok(mut cached) = RUNTIME.lock() {
+                    *cached = None;
+                }
+                resolve(app);
+                emit_progress(app, "installed", 0, None);
+                return;
+            }
+            Err(e) => eprintln!("js_runtime: Attempt {} of {} failed: {}", attempt, MAX_ATTEMPTS, e),
+        }
+        if attempt < MAX_ATTEMPTS {
+            std::thread::sleep(Duration::from_secs(2u64.pow(attempt)));
+        }
+    }
+    emit_progress(app, "failed", 0, None);
+}
+
+fn emit_progress(app: &impl Host, status: &str, downloaded: u64, total: Option<u64>) {
+    app.emit_event("js-runtime-install-progress", serde_json::json!({
+        "status": status,
+        "downloaded": downloaded,
+        "total": total,
+        "progress": total.filter(|t| *t > 0).map(|t| downloaded as f64 * 100.0 / t as f64),
+    }));
+}
+
+/// Forget the resolved runtime and yt-dlp, e.g. after the settings changed or an update
+pub fn reset() {
+    if let Ok(mut cached) = RUNTIME.lock() {
+        *cached = None;
+    }
+    if let Ok(mut cached) = YTDLP_SUPPORT.lock() {
+        *cached = None;
+    }
+}
+
+/// Release tags are dates, so they compare as strings
+fn accepts_runtimes(ytdlp_version: &str) -> bool {
+    ytdlp_version >= MIN_YTDLP_VERSION
+}
+
+fn ytdlp_supports_runtimes(ytdlp: &Path) -> bool {
+    if let Ok(cached) = YTDLP_SUPPORT.lock() {
+        if let Some((_, supported)) = cached.as_ref().filter(|(path, _)| path == ytdlp) {
+            return *supported;
+        }
+    }
+
+    let supported = crate::installed_ytdlp_version(ytdlp)
+        .is_some_and(|version| accepts_runtimes(&version));
+    if let Ok(mut cached) = YTDLP_SUPPORT.lock() {
+        *cached = Some((ytdlp.to_path_buf(), supported));
+    }
+    supported
+}
+
+/// Arguments that hand the runtime to yt-dlp, empty if it's too old to take them
+pub fn args(app: &impl Host, ytdlp: &Path) -> Vec<String> {
+    if !ytdlp_supports_runtimes(ytdlp) {
+        return Vec::new();
+    }
+    match resolve(app) {
+        Some((name, path)) => runtime_args(&name, &path),
+        None => Vec::new(),
+    }
+}
+
+fn runtime_args(name: &str, path: &Path) -> Vec<String> {
+    vec!["--js-runtimes".to_string(), format!("{}:{}", name, path.display())]
+}
+
+pub fn status(app: &impl Host, ytdlp: &Path) -> RuntimeStatus {
+    let runtime = resolve(app);
+    RuntimeStatus {
+        supported: ytdlp_supports_runtimes(ytdlp),
+        name: runtime.as_ref().map(|(name, _)| name.clone()),
+        path: runtime.map(|(_, path)| path.to_string_lossy().to_string()),
+    }
+}
+
+/// Download the latest Deno release (or from the mirror) into the local data dir
+/// The archive is streamed to a temp file and verified there, like the yt-dlp download
+fn download_deno(app: &impl Host) -> Result<PathBuf, String> {
+    let asset = deno_asset_name()
+        .ok_or_else(|| "Failed to install Deno: no Deno build for this platform".to_string())?;
+    let base = install::mirror(&settings::get()).unwrap_or("https://github.com").to_string();
+    let url = format!("{}/denoland/deno/releases/latest/download/{}", base, asset);
+    println!("js_runtime: Installing Deno from {}", url);
+
+    let target = managed_deno(app)?;
+    let dir = target.parent().ok_or_else(|| "Failed to install Deno: invalid path".to_string())?;
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+    install::remove_stale_temp_files(dir, &executable("deno"));
+
+    let archive = install::temp_path(dir, &executable("deno"));
+    let temp = install::temp_path(dir, &executable("deno"));
+    let result = (|| {
+        emit_progress(app, "downloading", 0, None);
+        let checksum = install::stream_to_file(&url, &archive, "Deno", |downloaded, total| {
+            emit_progress(app, "downloading", downloaded, total)
+        })?;
+
+        emit_progress(app, "verifying", 0, None);
+        verify_archive(asset, &fetch_checksum_file(&format!("{}.sha256sum", url))?, &checksum)?;
+
+        let mut zip = File::open(&archive)
+            .map_err(|e| e.to_string())
+            .and_then(|file| zip::ZipArchive::new(file).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Failed to install Deno: {}", e))?;
+        let mut entry = zip.by_name(&executable("deno"))
+            .map_err(|e| format!("Failed to install Deno: {}", e))?;
+        let mut file = File::create(&temp)
+            .map_err(|e| format!("Failed to create file: {}", e))?;
+        std::io::copy(&mut entry, &mut file)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+
+        install::install_executable(&temp, &target, "Deno")
+    })();
+
+    let _ = fs::remove_file(&archive);
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    println!("js_runtime: Installed Deno to {:?}", target);
+    Ok(target)
+}
+
+/// The `.sha256sum` file published next to each Deno release asset
+fn fetch_checksum_file(url: &str) -> Result<String, String> {
+    let (source, _) = install::open_download(url, "Deno checksum")?;
+    let mut contents = String::new();
+    // It holds one hash and a file name; anything longer isn't one
+    source.take(4096).read_to_string(&mut contents)
+        .map_err(|e| format!("Failed to download Deno checksum: {}", e))?;
+    Ok(contents)
+}
+
+/// Compare the downloaded archive's SHA-256 with the published `.sha256sum` contents
+fn verify_archive(asset: &str, sums: &str, actual: &str) -> Result<(), String> {
+    // The checksum file holds just the hash and the file name
+    let expected = sums
+        .split_whitespace()
+        .find(|token| token.len() == 64 && token.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(|hash| hash.to_lowercase())
+        .ok_or_else(|| format!("Failed to verify Deno download: {}.sha256sum has no checksum", asset))?;
+    if actual != expected {
+        return Err(format!(
+            "Failed to verify Deno download: checksum mismatch for {} (expected {}, got {}). The file was not installed.",
+            asset, expected, actual
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wanted_runtimes_follow_setting() {
+        let cases: [(&str, &[&str]); 5] = [
+            ("auto", &["deno", "node", "bun"]),
+            ("none", &[]),
+            ("deno", &["deno"]),
+            ("node", &["node"]),
+            ("bun", &["bun"]),
+        ];
+
+        for (setting, expected) in cases {
+            assert_eq!(wanted_runtimes(setting), expected, "runtimes for {:?}", setting);
+        }
+    }
+
+    #[test]
+    fn runtimes_need_recent_ytdlp() {
+        let cases = [
+            ("2025.11.12", true),
+            ("2025.11.12.232908", true),
+            ("2026.01.05", true),
+            ("2025.10.22", false),
+            ("2024.12.31.120000", false),
+        ];
+
+        for (version, expected) in cases {
+            assert_eq!(accepts_runtimes(version), expected, "yt-dlp {}", version);
+        }
+    }
+
+    #[test]
+    fn builds_js_runtimes_argument() {
+        let path = Path::new("/opt/My Tools/deno");
+        assert_eq!(
+            runtime_args("deno", path),
+            ["--js-runtimes".to_string(), format!("deno:{}", path.display())]
+        );
+    }
+
+    #[test]
+    fn verifies_archive_checksum() {
+        let hash = "a".repeat(64);
+        let other = "b".repeat(64);
+        let asset = "deno-x86_64-unknown-linux-gnu.zip";
+
+        let cases = [
+            (format!("{}  {}\n", hash, asset), true),
+            (format!("{}  {}\n", hash.to_uppercase(), asset), true),
+            (format!("{}  {}\n", other, asset), false),
+            (format!("{}\n", asset), false),
+            ("<html>Not Found</html>".to_string(), false),
+            (String::new(), false),
+        ];
+
+        for (sums, ok) in cases {
+            assert_eq!(verify_archive(asset, &sums, &hash).is_ok(), ok, "sums {:?}", sums);
+        }
+    }
+}
@@ -1,13 +1,37 @@
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter};
 use uuid::Uuid;
 
+mod api;
+mod auto_update;
+mod cli;
+mod clipboard;
+mod deep_link;
+mod errors;
+mod extractor_args;
+mod history;
+mod host;
+mod import;
+mod install;
+mod js_runtime;
+mod metadata_cache;
+mod request_profile;
+mod settings;
+mod thumbnails;
+mod update;
+mod urls;
+mod verify;
+
+pub use cli::run as run_cli;
+pub use settings::Settings;
+use host::Host;
+
 // Windows-specific: prevent console windows from appearing for child processes
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -24,20 +48,20 @@ fn ytdlp_binary_name() -> &'static str {
     return "yt-dlp";
 }
 
-/// Get the platform-specific yt-dlp download URL
-fn ytdlp_download_url() -> &'static str {
+/// Get the name of the platform-specific yt-dlp release asset
+fn ytdlp_asset_name() -> &'static str {
     #[cfg(target_os = "windows")]
-    return "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp.exe";
+    return "yt-dlp.exe";
 
     #[cfg(target_os = "macos")]
-    return "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp_macos";
+    return "yt-dlp_macos";
 
     #[cfg(target_os = "linux")]
-    return "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp_linux";
+    return "yt-dlp_linux";
 
     // Fallback for other platforms
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    return "https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp";
+    return "yt-dlp";
 }
 
 // Download state tracking
@@ -54,8 +78,13 @@ pub struct DownloadItem {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistInfo {
+    pub id: Option<String>,
     pub title: String,
-    pub video_count: u32,
+    pub uploader: Option<String>,
+    pub description: Option<String>,
+    pub thumbnail: Option<String>,
+    pub video_count: u32,        // Entries actually returned
+    pub total_count: Option<u32>, // Entries the playlist reports, may include unavailable videos
     pub videos: Vec<VideoInfo>,
 }
 
@@ -64,8 +93,88 @@ pub struct VideoInfo {
     pub id: String,
     pub title: String,
     pub url: String,
-    pub duration: Option<String>,
+    pub duration: Option<String>,        // Formatted as "M:SS" or "H:MM:SS"
+    pub duration_seconds: Option<f64>,
     pub thumbnail: Option<String>,
+    pub local_thumbnail: Option<String>, // Cached copy served through the thumb:// protocol
+    pub channel: Option<String>,
+    pub channel_id: Option<String>,
+    pub upload_date: Option<String>,     // YYYYMMDD, as reported by yt-dlp
+    pub view_count: Option<u64>,
+    pub like_count: Option<u64>,
+    pub description: Option<String>,
+    pub availability: Option<String>,    // public, unlisted, private, needs_auth, ...
+    pub live_status: Option<String>,     // not_live, is_live, is_upcoming, was_live, post_live
+    pub age_limit: Option<u32>,
+}
+
+impl VideoInfo {
+    /// Build from a yt-dlp JSON object (full `--dump-json` output or a flat playlist entry)
+    fn from_json(video_data: &serde_json::Value, url: String) -> Self {
+        let str_field = |name: &str| {
+            video_data.get(name)
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+        let u64_field = |name: &str| video_data.get(name).and_then(|v| v.as_u64());
+
+        let id = str_field("id").unwrap_or_else(|| "unknown".to_string());
+        let thumbnail = flat_entry_thumbnail(video_data);
+        // yt-dlp emits duration as a number of seconds (sometimes fractional)
+        let duration_seconds = video_data.get("duration").and_then(|v| v.as_f64());
+
+        Self {
+            title: str_field("title").unwrap_or_else(|| "Unknown".to_string()),
+            url,
+            duration: duration_seconds.map(format_duration),
+            duration_seconds,
+            local_thumbnail: thumbnails::register(&id, thumbnail.as_deref()),
+            thumbnail,
+            channel: str_field("channel").or_else(|| str_field("uploader")),
+            channel_id: str_field("channel_id").or_else(|| str_field("uploader_id")),
+            upload_date: str_field("upload_date"),
+            view_count: u64_field("view_count"),
+            like_count: u64_field("like_count"),
+            description: str_field("description"),
+            availability: str_field("availability"),
+            live_status: str_field("live_status"),
+            age_limit: u64_field("age_limit").map(|v| v as u32),
+            id,
+        }
+    }
+}
+
+/// Format seconds as "M:SS", or "H:MM:SS" for an hour or longer
+fn format_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as u64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+// A section of a video to download, timestamps as "SS", "MM:SS" or "HH:MM:SS"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: String,
+    pub end: Option<String>, // None = until the end of the video
+}
+
+// Optional per-download settings beyond type and quality
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadOptions {
+    /// Record a livestream: "start" (from the beginning) or "now" (from the current point)
+    pub live_mode: Option<String>,
+    /// Poll interval in seconds while waiting for a scheduled premiere/stream to start
+    pub wait_for_video: Option<u32>,
+    /// Only download these time ranges of the video (one file per range)
+    pub sections: Option<Vec<TimeRange>>,
+    /// Re-encode around the cut points so sections start exactly on the requested frame
+    pub precise_cuts: Option<bool>,
 }
 
 // Global state for active downloads
@@ -79,14 +188,43 @@ struct ActiveDownload {
     output_path: String,
     title: String,
     cancelled: bool,  // Flag to track if download was cancelled by user
+    live: bool,       // Livestream recording: report elapsed time and bytes instead of a percentage
+    stopping: bool,   // Flag to track if a live recording was asked to stop and finalize
+    job: DownloadJob, // What was requested, to start it again after a yt-dlp update
+    files: Vec<PathBuf>, // Destinations yt-dlp announced, for the size of live recordings
 }
 
 impl ActiveDownload {
-    fn new(child: std::process::Child, stdout: Option<std::process::ChildStdout>, stderr: Option<std::process::ChildStderr>, output_path: String, title: String) -> Self {
-        Self { child, stdout, stderr, output_path, title, cancelled: false }
+    fn new(child: std::process::Child, stdout: Option<std::process::ChildStdout>, stderr: Option<std::process::ChildStderr>, job: DownloadJob, live: bool) -> Self {
+        Self {
+            child,
+            stdout,
+            stderr,
+            output_path: job.output_path.clone(),
+            title: job.title.clone(),
+            cancelled: false,
+            live,
+            stopping: false,
+            job,
+            files: Vec::new(),
+        }
     }
 }
 
+// The arguments of a download, as passed to start_download
+#[derive(Debug, Clone)]
+struct DownloadJob {
+    url: String,
+    download_type: String,
+    output_path: String,
+    title: String,
+    video_quality: String,
+    options: Option<DownloadOptions>,
+    started_at: std::time::SystemTime,
+    /// Already started again after an automatic yt-dlp update, don't retry a second time
+    retried: bool,
+}
+
 // Lazy static for global registry
 use once_cell::sync::Lazy;
 
@@ -94,136 +232,256 @@ static DOWNLOAD_REGISTRY: Lazy<DownloadRegistry> = Lazy::new(|| {
     Arc::new(Mutex::new(HashMap::new()))
 });
 
-// YTDLP_PATH will store the path to yt-dlp executable (thread-safe)
-static YTDLP_PATH: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+/// Whether any download is currently running
+fn downloads_active() -> bool {
+    DOWNLOAD_REGISTRY.lock().map(|registry| !registry.is_empty()).unwrap_or(true)
+}
+
+/// Whether `download_id` is still registered: running, or waiting for its retry after a yt-dlp update
+fn download_active(download_id: &str) -> bool {
+    DOWNLOAD_REGISTRY.lock().map(|registry| registry.contains_key(download_id)).unwrap_or(true)
+}
+
+/// Whether any download other than `download_id` is currently running
+fn other_downloads_active(download_id: &str) -> bool {
+    DOWNLOAD_REGISTRY.lock().map(|registry| registry.keys().any(|id| id != download_id)).unwrap_or(true)
+}
+
+// Running playlist enumerations by request id, so they can be cancelled
+static METADATA_FETCHES: Lazy<Mutex<HashMap<String, std::process::Child>>> = Lazy::new(|| {
+    Mutex::new(HashMap::new())
+});
+
+// Playlist entries are sent to the frontend in batches of this size (or every 250ms)
+const METADATA_BATCH_SIZE: usize = 50;
+
+/// Where the yt-dlp in use comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YtdlpSource {
+    /// Shipped in the app's resource dir
+    Bundled,
+    /// Downloaded and updated by Verdl
+    Managed,
+    /// Found on PATH (distro package, pipx, ...)
+    Path,
+    /// File chosen in settings
+    Custom,
+}
+
+// YTDLP_PATH will store the path to yt-dlp executable and its source (thread-safe)
+static YTDLP_PATH: Lazy<Mutex<Option<(PathBuf, YtdlpSource)>>> = Lazy::new(|| Mutex::new(None));
 
 /// Get the path to the yt-dlp executable
-/// Will check in order: resource dir -> local data dir -> download if needed
-fn get_ytdlp_path(app: &AppHandle) -> Result<PathBuf, String> {
+fn get_ytdlp_path(app: &impl Host) -> Result<PathBuf, String> {
+    resolve_ytdlp(app).map(|(path, _)| path)
+}
+
+/// Find yt-dlp according to the `ytdlp_source` setting
+/// Managed: resource dir -> local data dir -> download if needed
+/// PATH and custom files are only accepted if `--version` runs
+fn resolve_ytdlp(app: &impl Host) -> Result<(PathBuf, YtdlpSource), String> {
     // Return cached path if available
     {
         let cached = YTDLP_PATH.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
-        if let Some(ref path) = *cached {
+        if let Some((path, source)) = cached.as_ref() {
             if path.exists() {
-                return Ok(path.clone());
+                return Ok((path.clone(), *source));
             }
         }
     }
 
-    // Try resource directory first (bundled with app)
-    let resource_path = app.path().resource_dir()
-        .map_err(|e| format!("Failed to get resource dir: {}", e))?
-        .join(ytdlp_binary_name());
+    let settings = settings::get();
+    let resolved = match settings.ytdlp_source.as_str() {
+        "path" => {
+            let path = find_on_path(ytdlp_binary_name())
+                .ok_or_else(|| "Failed to execute yt-dlp: not found on PATH".to_string())?;
+            (validate_ytdlp(&path)?, YtdlpSource::Path)
+        }
+        "custom" => {
+            let path = PathBuf::from(settings.ytdlp_custom_path.unwrap_or_default());
+            (validate_ytdlp(&path)?, YtdlpSource::Custom)
+        }
+        _ => managed_ytdlp(app)?,
+    };
+
+    *YTDLP_PATH.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))? = Some(resolved.clone());
+    Ok(resolved)
+}
 
-    if resource_path.exists() {
-        *YTDLP_PATH.lock()
-            .map_err(|e| format!("Failed to acquire lock: {}", e))? = Some(resource_path.clone());
-        return Ok(resource_path);
+/// Forget the resolved yt-dlp, e.g. after the source setting changed
+fn reset_ytdlp_path() {
+    if let Ok(mut cached) = YTDLP_PATH.lock() {
+        *cached = None;
+    }
+    if let Ok(mut extractors) = EXTRACTORS.lock() {
+        extractors.clear();
     }
+    js_runtime::reset();
+}
 
-    // Try local data directory
-    let local_data_dir = app.path().app_local_data_dir()
-        .map_err(|e| format!("Failed to get local data dir: {}", e))?;
+fn managed_ytdlp(app: &impl Host) -> Result<(PathBuf, YtdlpSource), String> {
+    let local_data_dir = app.local_data_dir()?;
 
+    // Try local data directory first, updates of a bundled yt-dlp are installed there
     let local_ytdlp = local_data_dir.join(ytdlp_binary_name());
     if local_ytdlp.exists() {
-        *YTDLP_PATH.lock()
-            .map_err(|e| format!("Failed to acquire lock: {}", e))? = Some(local_ytdlp.clone());
-        return Ok(local_ytdlp);
+        return Ok((local_ytdlp, YtdlpSource::Managed));
     }
 
-    // Download yt-dlp to local data directory
-    download_ytdlp(&local_data_dir)?;
+    // Try resource directory (bundled with app)
+    let resource_path = app.resource_dir()?.join(ytdlp_binary_name());
+    if resource_path.exists() {
+        return Ok((resource_path, YtdlpSource::Bundled));
+    }
 
-    *YTDLP_PATH.lock()
-        .map_err(|e| format!("Failed to acquire lock: {}", e))? = Some(local_ytdlp.clone());
-    Ok(local_ytdlp)
+    // Download yt-dlp to local data directory
+    install::download_ytdlp(app, &install::download_url(&settings::get()), &local_data_dir)?;
+    Ok((local_ytdlp, YtdlpSource::Managed))
 }
 
-/// Download yt-dlp binary to the specified directory
-fn download_ytdlp(target_dir: &Path) -> Result<(), String> {
-    println!("download_ytdlp: Starting download to {:?}", target_dir);
+/// First match for an executable in the PATH directories
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
 
-    fs::create_dir_all(target_dir)
-        .map_err(|e| format!("Failed to create directory: {}", e))?;
+/// Make sure a user-provided yt-dlp exists and runs
+fn validate_ytdlp(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_file() {
+        return Err(format!("Failed to execute yt-dlp: {} does not exist", path.display()));
+    }
+    match installed_ytdlp_version(path) {
+        Some(version) => {
+            println!("Using yt-dlp {} at {:?}", version, path);
+            Ok(path.to_path_buf())
+        }
+        None => Err(format!("Failed to execute yt-dlp: {} --version did not succeed", path.display())),
+    }
+}
 
-    let ytdlp_filename = ytdlp_binary_name();
-    let ytdlp_path = target_dir.join(ytdlp_filename);
+// Extractor yt-dlp picks for a URL, remembered per URL
+static EXTRACTORS: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
-    // Download the platform-specific binary
-    let download_url = ytdlp_download_url();
+/// URLs remembered at most in EXTRACTORS before it is cleared
+const MAX_EXTRACTOR_MATCHES: usize = 500;
 
-    println!("download_ytdlp: Downloading from {}", download_url);
+// Set while yt-dlp is being asked about a URL; one at a time, as URLs are validated while typed
+static EXTRACTOR_PROBE_RUNNING: AtomicBool = AtomicBool::new(false);
 
-    let response = minreq::get(download_url)
-        .with_timeout(30) // 30 second timeout
-        .send()
-        .map_err(|e| {
-            println!("download_ytdlp: Download failed: {}", e);
-            format!("Failed to download yt-dlp: {}", e)
-        })?;
+/// Validate a URL against the site allowlist with strict pattern matching
+/// Prevents command injection and ensures only URLs from allowed sites are accepted.
+/// yt-dlp only ever receives the canonical form returned here
+fn parse_supported_url(app: &impl Host, url: &str) -> Result<urls::ParsedUrl, String> {
+    let settings = settings::get();
 
-    println!("download_ytdlp: Got response with status code {}", response.status_code);
+    let parsed = urls::parse_allowed(url.trim(), &settings.allowed_sites).ok_or_else(|| {
+        format!("Invalid URL. Only URLs from allowed sites ({}) are allowed.", settings.allowed_sites.join(", "))
+    })?;
 
-    if response.status_code < 200 || response.status_code >= 300 {
-        return Err(format!("Failed to download yt-dlp: HTTP {}", response.status_code));
+    if parsed.kind == urls::UrlKind::Other && settings.verify_extractors {
+        let host = urls::host(&parsed.canonical_url).unwrap_or_default();
+        if !has_extractor_for(app, &parsed.canonical_url) {
+            return Err(format!("yt-dlp has no extractor for {}", host));
+        }
     }
 
-    let content = response.as_bytes();
-    println!("download_ytdlp: Downloaded {} bytes", content.len());
-
-    let mut file = File::create(&ytdlp_path)
-        .map_err(|e| format!("Failed to create file: {}", e))?;
-
-    file.write_all(content)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(parsed)
+}
 
-    println!("download_ytdlp: File written to {:?}", ytdlp_path);
+/// Whether yt-dlp has a site extractor for `url` rather than falling back to the generic one
+/// Never waits for yt-dlp: a URL it hasn't been asked about yet counts as supported (the
+/// allowlist already matched) while it is asked in the background for the next check
+fn has_extractor_for(app: &impl Host, url: &str) -> bool {
+    if let Some(extractor) = EXTRACTORS.lock().ok().and_then(|matches| matches.get(url).cloned()) {
+        return extractor != "generic";
+    }
 
-    // On Unix-like systems (macOS/Linux), make the binary executable
-    #[cfg(not(target_os = "windows"))]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&ytdlp_path)
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .permissions();
-        perms.set_mode(0o755); // rwxr-xr-x
-        fs::set_permissions(&ytdlp_path, perms)
-            .map_err(|e| format!("Failed to set executable permissions: {}", e))?;
+    if !EXTRACTOR_PROBE_RUNNING.swap(true, Ordering::SeqCst) {
+        let app = app.clone();
+        let url = url.to_string();
+        std::thread::spawn(move || {
+            if let Err(e) = probe_extractor(&app, &url) {
+                eprintln!("{}", e);
+            }
+            EXTRACTOR_PROBE_RUNNING.store(false, Ordering::SeqCst);
+        });
     }
+    true
+}
 
-    println!("download_ytdlp: Successfully downloaded yt-dlp");
+/// Ask yt-dlp which extractor it picks for `url` and remember it in EXTRACTORS
+/// yt-dlp reports the extractor whose suitable() matched as "[name] Extracting URL: ..."
+/// before making any request, so it is stopped as soon as that line appears
+fn probe_extractor(app: &impl Host, url: &str) -> Result<(), String> {
+    use std::io::{BufRead, BufReader};
 
+    let ytdlp = get_ytdlp_path(app)?;
+    let mut child = ytdlp_command(app, &ytdlp, url)
+        .args(["--simulate", "--flat-playlist", "--no-warnings", url])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+
+    let extractor = child.stdout.take().and_then(|stdout| {
+        BufReader::new(stdout).lines()
+            .map_while(Result::ok)
+            .find_map(|line| extractor_name(&line))
+    });
+    let _ = child.kill();
+    let _ = child.wait();
+
+    let extractor = extractor
+        .ok_or_else(|| "Failed to check yt-dlp extractors: yt-dlp didn't report one".to_string())?;
+    if let Ok(mut matches) = EXTRACTORS.lock() {
+        if matches.len() >= MAX_EXTRACTOR_MATCHES {
+            matches.clear();
+        }
+        matches.insert(url.to_string(), extractor);
+    }
     Ok(())
 }
 
-/// Validate YouTube URL with strict pattern matching
-/// Prevents command injection and ensures only valid YouTube URLs are accepted
-fn is_valid_youtube_url(url: &str) -> bool {
-    // Check basic URL structure
-    if !url.starts_with("http://") && !url.starts_with("https://") {
-        return false;
-    }
+/// Extractor name from yt-dlp's "[name] Extracting URL: ..." line, lowercase
+fn extractor_name(line: &str) -> Option<String> {
+    let (name, rest) = line.strip_prefix('[')?.split_once(']')?;
+    rest.trim_start().starts_with("Extracting URL:").then(|| name.to_lowercase())
+}
 
-    // Use regex to validate YouTube URL patterns
-    // Matches: youtube.com/watch?v=ID or youtu.be/ID
-    let youtube_pattern = regex::Regex::new(
-        r"^(https?://)?(www\.)?(youtube\.com/watch\?v=[\w-]+|youtu\.be/[\w-]+)"
-    ).map_err(|_| false);
+/// yt-dlp command for a request to `url`, with the request profile, JavaScript runtime
+/// and extractor arguments from settings; every call that reaches a site starts here
+fn ytdlp_command(app: &impl Host, ytdlp: &Path, url: &str) -> Command {
+    let settings = settings::get();
+    let mut cmd = local_ytdlp_command(ytdlp);
+    cmd.args(settings.request_profile.args(url))
+        .args(js_runtime::args(app, ytdlp))
+        .args(extractor_args::args(&settings));
+    cmd
+}
 
-    if youtube_pattern.is_err() {
-        return false;
-    }
+/// yt-dlp command without any request settings, only for calls that don't talk to a video
+/// site (`--version`, `--update-to`): there is no URL to build a profile for, and the runtime
+/// and extractor arguments would be ignored
+fn local_ytdlp_command(ytdlp: &Path) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(ytdlp);
+
+    // Windows: prevent console window
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
 
-    let re = youtube_pattern.unwrap();
-    re.is_match(url)
+    cmd
 }
 
 /// Validate and sanitize output path to prevent path traversal attacks
 /// Basic validation only - users have full control over download locations
 #[allow(unused_variables)]
-fn validate_output_path(app: &AppHandle, path: &str) -> Result<String, String> {
+fn validate_output_path(app: &impl Host, path: &str) -> Result<String, String> {
     use std::path::PathBuf;
 
     let path_obj = PathBuf::from(path);
@@ -256,149 +514,390 @@ fn validate_output_path(app: &AppHandle, path: &str) -> Result<String, String> {
     Ok(absolute_path.to_string_lossy().to_string())
 }
 
-// Helper to check if yt-dlp is installed
+/// Parse a timestamp ("SS", "MM:SS" or "HH:MM:SS", fractional seconds allowed) into seconds
+fn parse_timestamp(timestamp: &str) -> Result<f64, String> {
+    let parts: Vec<&str> = timestamp.trim().split(':').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(format!("Invalid timestamp '{}'", timestamp));
+    }
+
+    let mut seconds = 0.0;
+    for (i, part) in parts.iter().enumerate() {
+        let is_last = i == parts.len() - 1;
+        let value = part.parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .ok_or_else(|| format!("Invalid timestamp '{}'", timestamp))?;
+
+        // Only the seconds field may be fractional, and minutes/seconds must stay below 60
+        if (!is_last && value.fract() != 0.0) || (i > 0 && value >= 60.0) {
+            return Err(format!("Invalid timestamp '{}'", timestamp));
+        }
+        seconds = seconds * 60.0 + value;
+    }
+
+    Ok(seconds)
+}
+
+/// Validate requested sections and turn them into yt-dlp `--download-sections` values
+/// Ranges are checked against the video duration when it is known
+fn build_download_sections(sections: &[TimeRange], duration: Option<f64>) -> Result<Vec<String>, String> {
+    let mut specs = Vec::new();
+
+    for range in sections {
+        let start = parse_timestamp(&range.start)?;
+        let end = match &range.end {
+            Some(end) => Some(parse_timestamp(end)?),
+            None => None,
+        };
+
+        if let Some(end) = end {
+            if end <= start {
+                return Err(format!("Invalid range {}-{}: end must be after start", start, end));
+            }
+        }
+
+        if let Some(duration) = duration {
+            if start >= duration || end.is_some_and(|end| end > duration) {
+                return Err(format!(
+                    "Range {}-{} is outside the video duration ({}s)",
+                    range.start,
+                    range.end.as_deref().unwrap_or("end"),
+                    duration
+                ));
+            }
+        }
+
+        specs.push(format!("*{}-{}", start, end.map(|e| e.to_string()).unwrap_or_else(|| "inf".to_string())));
+    }
+
+    Ok(specs)
+}
+
+/// What check_ytdlp_installed reports
+#[derive(Debug, Clone, Serialize)]
+pub struct YtdlpStatus {
+    pub installed: bool,
+    pub source: Option<YtdlpSource>,
+    pub path: Option<String>,
+    pub version: Option<String>,
+    pub error: Option<String>,
+    /// JavaScript runtime handed to yt-dlp for YouTube challenges
+    pub runtime: Option<js_runtime::RuntimeStatus>,
+}
+
+// Helper to check if yt-dlp is installed, and which one is used
 #[tauri::command]
-async fn check_ytdlp_installed(app: AppHandle) -> Result<bool, String> {
+async fn check_ytdlp_installed(app: AppHandle) -> Result<YtdlpStatus, String> {
     println!("check_ytdlp_installed: Starting check...");
 
-    match get_ytdlp_path(&app) {
-        Ok(path) => {
-            println!("check_ytdlp_installed: Found yt-dlp at {:?}", path);
+    match resolve_ytdlp(&app) {
+        Ok((path, source)) => {
+            println!("check_ytdlp_installed: Found yt-dlp at {:?} ({:?})", path, source);
 
             // Verify it works by running --version
-            let mut cmd = Command::new(&path);
-
-            // Windows: prevent console window
-            #[cfg(target_os = "windows")]
-            cmd.creation_flags(CREATE_NO_WINDOW);
-
-            let result = cmd
-                .arg("--version")
-                .output();
+            let version = installed_ytdlp_version(&path);
+            if version.is_some() {
+                println!("check_ytdlp_installed: yt-dlp is ready");
+            } else {
+                println!("check_ytdlp_installed: yt-dlp --version failed");
+            }
 
-            match &result {
-                Ok(output) => {
-                    println!("check_ytdlp_installed: Command output status: {}", output.status);
-                    if output.status.success() {
-                        println!("check_ytdlp_installed: yt-dlp is ready");
-                    } else {
-                        println!("check_ytdlp_installed: Command failed with status: {}", output.status);
-                    }
-                }
-                Err(e) => {
-                    println!("check_ytdlp_installed: Failed to run command: {}", e);
-                }
+            let runtime = js_runtime::status(&app, &path);
+            if runtime.name.is_none() {
+                println!("check_ytdlp_installed: No JavaScript runtime for YouTube challenges");
             }
 
-            Ok(result.is_ok() && result.map(|o| o.status.success()).unwrap_or(false))
+            Ok(YtdlpStatus {
+                installed: version.is_some(),
+                source: Some(source),
+                path: Some(path.to_string_lossy().to_string()),
+                version,
+                error: None,
+                runtime: Some(runtime),
+            })
         }
         Err(e) => {
             println!("check_ytdlp_installed: Error getting yt-dlp path: {}", e);
-            Ok(false)
+            Ok(YtdlpStatus { installed: false, source: None, path: None, version: None, error: Some(e), runtime: None })
         }
     }
 }
 
 // Fetch playlist/video metadata
+// Results are cached for the configured TTL unless force_refresh is set
+// Playlists emit "metadata-entry" events while they are being enumerated; pass a
+// request_id to be able to cancel the enumeration with cancel_metadata_fetch
 #[tauri::command]
-async fn fetch_metadata(app: AppHandle, url: String) -> Result<PlaylistInfo, String> {
-    let ytdlp = get_ytdlp_path(&app)?;
-
-    // Check if it's a playlist or single video
-    let is_playlist = url.contains("playlist") || url.contains("list=");
-
-    if is_playlist {
-        fetch_playlist_metadata(&ytdlp, url).await
-    } else {
-        fetch_single_video_metadata(&ytdlp, url).await
-    }
+async fn fetch_metadata(
+    app: AppHandle,
+    url: String,
+    force_refresh: Option<bool>,
+    request_id: Option<String>,
+) -> Result<PlaylistInfo, String> {
+    load_metadata(&app, url, force_refresh, request_id).await
 }
 
-async fn fetch_playlist_metadata(ytdlp: &Path, url: String) -> Result<PlaylistInfo, String> {
-    let mut cmd = Command::new(ytdlp);
+async fn load_metadata(
+    app: &impl Host,
+    url: String,
+    force_refresh: Option<bool>,
+    request_id: Option<String>,
+) -> Result<PlaylistInfo, String> {
+    let ttl = std::time::Duration::from_secs(settings::get().metadata_cache_ttl_secs);
+
+    // Validated first, so the cache never answers for a URL the allowlist now rejects
+    let parsed = parse_supported_url(app, &url)?;
+
+    if !force_refresh.unwrap_or(false) {
+        if let Some(cached) = metadata_cache::get(&url, ttl) {
+            println!("fetch_metadata: Using cached metadata for {}", url);
+            return Ok(cached);
+        }
+    }
 
-    // Windows: prevent console window
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(CREATE_NO_WINDOW);
+    let ytdlp = get_ytdlp_path(app)?;
 
-    let user_agent = if cfg!(target_os = "macos") {
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+    // Playlists, channels and searches are enumerated as a list of entries
+    let (info, complete) = if parsed.kind.is_collection() {
+        let request_id = request_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        fetch_playlist_metadata(app, &ytdlp, parsed.enumeration_url(), &request_id).await?
     } else {
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+        (fetch_single_video_metadata(app, &ytdlp, parsed.canonical_url).await?, true)
     };
 
-    let output = cmd
+    // Entries from a failed enumeration are shown, but not cached as the whole playlist
+    if complete {
+        metadata_cache::insert(&url, info.clone(), ttl);
+    }
+    Ok(info)
+}
+
+/// Enumerate a playlist, channel or search, returning what was found and whether the
+/// enumeration completed (a failed one still returns the entries it got before failing)
+async fn fetch_playlist_metadata(app: &impl Host, ytdlp: &Path, url: String, request_id: &str) -> Result<(PlaylistInfo, bool), String> {
+    use std::io::Read;
+
+    let mut child = ytdlp_command(app, ytdlp, &url)
         .args([
+            // One JSON line per entry as soon as it is enumerated, then the
+            // playlist-level fields once enumeration has finished
             "--dump-json",
             "--flat-playlist",
-            "--user-agent", user_agent,
-            "--referer", "https://www.youtube.com/",
-            "--extractor-retries", "3",
-            "--socket-timeout", "10",
-            "--no-cache-dir",
+            "--print", "playlist:%()j",
             &url,
         ])
-        .output()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
 
-    if !output.status.success() {
-        let error_msg = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to fetch playlist: {}", error_msg));
-    }
+    let stdout = child.stdout.take()
+        .ok_or_else(|| "Failed to capture yt-dlp output".to_string())?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+    // Drain stderr on its own thread so warnings can't fill the pipe and stall yt-dlp
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        std::thread::spawn(move || {
+            let mut error_output = String::new();
+            let _ = stderr.read_to_string(&mut error_output);
+            error_output
+        })
+    });
+
+    METADATA_FETCHES.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .insert(request_id.to_string(), child);
+
+    // Reading yt-dlp's output blocks until the enumeration ends, keep it off the async runtime
+    let (app, request_id) = (app.clone(), request_id.to_string());
+    tauri::async_runtime::spawn_blocking(move || read_playlist_metadata(&app, stdout, stderr_reader, &request_id))
+        .await
+        .map_err(|e| format!("Failed to fetch playlist: {}", e))?
+}
+
+fn read_playlist_metadata(
+    app: &impl Host,
+    stdout: std::process::ChildStdout,
+    stderr_reader: Option<std::thread::JoinHandle<String>>,
+    request_id: &str,
+) -> Result<(PlaylistInfo, bool), String> {
+    use std::io::{BufRead, BufReader};
 
     let mut videos = Vec::new();
-    for line in lines {
-        if let Ok(video_data) = serde_json::from_str::<serde_json::Value>(line) {
-            videos.push(VideoInfo {
-                id: video_data.get("id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
-                title: video_data.get("title").and_then(|v| v.as_str()).unwrap_or("Unknown").to_string(),
-                url: format!("https://www.youtube.com/watch?v={}",
-                    video_data.get("id").and_then(|v| v.as_str()).unwrap_or("")),
-                duration: video_data.get("duration").and_then(|v| v.as_str()).map(String::from),
-                thumbnail: video_data.get("thumbnail").and_then(|v| v.as_str()).map(String::from),
-            });
+    let mut playlist_data = None;
+    let mut first_entry = None;
+    let mut batch = Vec::new();
+    let mut last_emit = std::time::Instant::now();
+
+    let emit_batch = |batch: &mut Vec<VideoInfo>, count: usize, total: Option<u64>| {
+        app.emit_event("metadata-entry", serde_json::json!({
+            "requestId": request_id,
+            "entries": std::mem::take(batch),
+            "count": count,
+            "total": total,
+        }));
+    };
+
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        let Ok(data) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+
+        if data.get("_type").and_then(|v| v.as_str()) == Some("playlist") {
+            playlist_data = Some(data);
+            continue;
+        }
+
+        // YouTube flat entries only carry an id; other sites give a page URL
+        let url = ["webpage_url", "url"].iter()
+            .filter_map(|field| data.get(*field).and_then(|v| v.as_str()))
+            .find(|u| u.starts_with("https://") || u.starts_with("http://"))
+            .map(String::from)
+            .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}",
+                data.get("id").and_then(|v| v.as_str()).unwrap_or("")));
+
+        // Nested playlists and channel tabs aren't videos, leave them out
+        if is_nested_collection(&data, &url) {
+            println!("fetch_metadata: Skipping nested playlist {}", url);
+            continue;
+        }
+        let video = VideoInfo::from_json(&data, url);
+        batch.push(video.clone());
+        videos.push(video);
+
+        let total = data.get("playlist_count").and_then(|v| v.as_u64());
+        if first_entry.is_none() {
+            first_entry = Some(data);
+        }
+
+        if batch.len() >= METADATA_BATCH_SIZE || last_emit.elapsed() >= std::time::Duration::from_millis(250) {
+            emit_batch(&mut batch, videos.len(), total);
+            last_emit = std::time::Instant::now();
         }
     }
 
-    let title = if videos.first().is_some() {
-        format!("Playlist with {} videos", videos.len())
-    } else {
-        "Empty Playlist".to_string()
+    // A missing registry entry means cancel_metadata_fetch killed the process
+    let child = METADATA_FETCHES.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .remove(request_id);
+    let Some(mut child) = child else {
+        return Err("Metadata fetch cancelled".to_string());
     };
 
-    Ok(PlaylistInfo {
-        title,
-        video_count: videos.len() as u32,
-        videos,
-    })
+    let status = child.wait()
+        .map_err(|e| format!("Failed to wait for yt-dlp: {}", e))?;
+    let error_output = stderr_reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+
+    if !status.success() && videos.is_empty() {
+        return Err(format!("Failed to fetch playlist: {}", error_output));
+    }
+    if !status.success() {
+        eprintln!("fetch_metadata: Enumeration failed after {} entries: {}", videos.len(), error_output.trim());
+    }
+
+    if !batch.is_empty() {
+        let total = first_entry.as_ref().and_then(|d| d.get("playlist_count")).and_then(|v| v.as_u64());
+        emit_batch(&mut batch, videos.len(), total);
+    }
+
+    // A single video (other sites' URLs take this path too) comes back as its full info
+    // dict, with no playlist line
+    if playlist_data.is_none() && videos.len() == 1 {
+        if let Some(entry) = first_entry.as_ref().filter(|entry| entry.get("_type").and_then(|v| v.as_str()) != Some("url")) {
+            let url = videos.remove(0).url;
+            return Ok((single_video_info(entry, url), status.success()));
+        }
+    }
+
+    // Fall back to the playlist_* fields every entry carries if the playlist line is missing
+    let playlist_data = playlist_data.unwrap_or_else(|| {
+        let entry = first_entry.unwrap_or_default();
+        let field = |name: &str| entry.get(name).cloned().unwrap_or(serde_json::Value::Null);
+        serde_json::json!({
+            "id": field("playlist_id"),
+            "title": field("playlist_title"),
+            "uploader": field("playlist_uploader"),
+            "playlist_count": field("playlist_count"),
+        })
+    });
+
+    Ok((playlist_info_from_json(&playlist_data, videos), status.success()))
 }
 
-async fn fetch_single_video_metadata(ytdlp: &Path, url: String) -> Result<PlaylistInfo, String> {
-    let mut cmd = Command::new(ytdlp);
+// Cancel a running playlist enumeration started by fetch_metadata
+#[tauri::command]
+async fn cancel_metadata_fetch(request_id: String) -> Result<bool, String> {
+    let child = METADATA_FETCHES.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .remove(&request_id);
+
+    match child {
+        Some(mut child) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            println!("Cancelled metadata fetch: {}", request_id);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
 
-    // Windows: prevent console window
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(CREATE_NO_WINDOW);
+/// Whether a flat playlist entry is itself a playlist, channel tab or search
+/// (the playlist's own line is `_type: playlist` and handled before this)
+fn is_nested_collection(entry: &serde_json::Value, url: &str) -> bool {
+    let ie_key = entry.get("ie_key").and_then(|v| v.as_str()).unwrap_or("");
+    ie_key.ends_with("Tab")
+        || ie_key.ends_with("Playlist")
+        || urls::parse(url).is_some_and(|parsed| matches!(
+            parsed.kind,
+            urls::UrlKind::Playlist | urls::UrlKind::Channel | urls::UrlKind::ChannelTab | urls::UrlKind::Search
+        ))
+}
 
-    let user_agent = if cfg!(target_os = "macos") {
-        "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
-    } else {
-        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+/// Playlist-level metadata from yt-dlp's `-J --flat-playlist` output
+fn playlist_info_from_json(playlist_data: &serde_json::Value, videos: Vec<VideoInfo>) -> PlaylistInfo {
+    let str_field = |name: &str| {
+        playlist_data.get(name)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(String::from)
     };
 
-    let output = cmd
-        .args([
-            "--dump-json",
-            "--user-agent", user_agent,
-            "--referer", "https://www.youtube.com/",
-            "--extractor-retries", "3",
-            "--socket-timeout", "10",
-            "--no-cache-dir",
-            &url,
-        ])
+    let title = str_field("title").unwrap_or_else(|| {
+        if videos.is_empty() { "Empty Playlist".to_string() } else { "Untitled Playlist".to_string() }
+    });
+
+    PlaylistInfo {
+        id: str_field("id"),
+        title,
+        uploader: str_field("uploader").or_else(|| str_field("channel")),
+        description: str_field("description"),
+        thumbnail: flat_entry_thumbnail(playlist_data),
+        video_count: videos.len() as u32,
+        total_count: playlist_data.get("playlist_count").and_then(|v| v.as_u64()).map(|v| v as u32),
+        videos,
+    }
+}
+
+// Flat playlist entries have no "thumbnail" field, only a "thumbnails" list (largest last)
+fn flat_entry_thumbnail(video_data: &serde_json::Value) -> Option<String> {
+    video_data.get("thumbnail")
+        .and_then(|v| v.as_str())
+        .or_else(|| {
+            video_data.get("thumbnails")
+                .and_then(|v| v.as_array())
+                .and_then(|thumbs| thumbs.last())
+                .and_then(|t| t.get("url"))
+                .and_then(|v| v.as_str())
+        })
+        .map(String::from)
+}
+
+async fn fetch_single_video_metadata(app: &impl Host, ytdlp: &Path, url: String) -> Result<PlaylistInfo, String> {
+    let output = ytdlp_command(app, ytdlp, &url)
+        .args(["--dump-json", &url])
         .output()
         .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
 
@@ -409,24 +908,28 @@ async fn fetch_single_video_metadata(ytdlp: &Path, url: String) -> Result<Playli
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     if let Ok(video_data) = serde_json::from_str::<serde_json::Value>(&stdout) {
-        let video = VideoInfo {
-            id: video_data.get("id").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
-            title: video_data.get("title").and_then(|v| v.as_str()).unwrap_or("Unknown").to_string(),
-            url: url.clone(),
-            duration: video_data.get("duration").and_then(|v| v.as_str()).map(String::from),
-            thumbnail: video_data.get("thumbnail").and_then(|v| v.as_str()).map(String::from),
-        };
-
-        Ok(PlaylistInfo {
-            title: video_data.get("title").and_then(|v| v.as_str()).unwrap_or("Unknown Video").to_string(),
-            video_count: 1,
-            videos: vec![video],
-        })
+        Ok(single_video_info(&video_data, url))
     } else {
         Err("Failed to parse video metadata".to_string())
     }
 }
 
+/// Metadata for a single video from yt-dlp's `--dump-json` output
+fn single_video_info(video_data: &serde_json::Value, url: String) -> PlaylistInfo {
+    let video = VideoInfo::from_json(video_data, url);
+
+    PlaylistInfo {
+        id: Some(video.id.clone()),
+        title: video_data.get("title").and_then(|v| v.as_str()).unwrap_or("Unknown Video").to_string(),
+        uploader: video.channel.clone(),
+        description: video.description.clone(),
+        thumbnail: video.thumbnail.clone(),
+        video_count: 1,
+        total_count: Some(1),
+        videos: vec![video],
+    }
+}
+
 // Download video(s)
 #[tauri::command]
 async fn download_video(
@@ -436,9 +939,41 @@ async fn download_video(
     output_path: String,
     title: String,
     video_quality: String,
+    options: Option<DownloadOptions>,
+) -> Result<String, String> {
+    start_download(&app, url, download_type, output_path, title, video_quality, options).await
+}
+
+/// Validate, spawn yt-dlp and start monitoring; returns the download id
+async fn start_download<H: Host>(
+    app: &H,
+    url: String,
+    download_type: String,
+    output_path: String,
+    title: String,
+    video_quality: String,
+    options: Option<DownloadOptions>,
 ) -> Result<String, String> {
-    let ytdlp = get_ytdlp_path(&app)?;
+    let job = DownloadJob {
+        url,
+        download_type,
+        output_path,
+        title,
+        video_quality,
+        options,
+        started_at: std::time::SystemTime::now(),
+        retried: false,
+    };
     let download_id = Uuid::new_v4().to_string();
+    start_download_job(app, download_id.clone(), job).await?;
+    Ok(download_id)
+}
+
+/// Start (or restart) a download under the given id
+async fn start_download_job<H: Host>(app: &H, download_id: String, job: DownloadJob) -> Result<(), String> {
+    let DownloadJob { url, download_type, output_path, title, video_quality, options, .. } = job.clone();
+    let options = options.unwrap_or_default();
+    let ytdlp = get_ytdlp_path(app)?;
 
     println!("=== Starting download ===");
     println!("URL: {}", url);
@@ -448,45 +983,67 @@ async fn download_video(
 
     // Validate URL with strict pattern matching
     println!("Validating URL...");
-    if !is_valid_youtube_url(&url) {
-        return Err("Invalid URL. Only YouTube URLs (youtube.com or youtu.be) are allowed.".to_string());
-    }
-    println!("URL validation passed");
+    let url = parse_supported_url(app, &url)?.canonical_url;
+    println!("URL validation passed: {}", url);
 
     // Validate and sanitize output path to prevent path traversal attacks
     println!("Validating output path...");
-    let validated_path = validate_output_path(&app, &output_path)?;
+    let validated_path = validate_output_path(app, &output_path)?;
     println!("Path validation passed: {}", validated_path);
     let output_dir = Path::new(&validated_path);
 
+    // Live mode: "start" records from the beginning of the stream, "now" from the current point
+    let live_from_start = match options.live_mode.as_deref() {
+        None => None,
+        Some("start") => Some(true),
+        Some("now") => Some(false),
+        Some(other) => return Err(format!("Invalid live mode '{}'. Expected 'start' or 'now'.", other)),
+    };
+    let is_live = live_from_start.is_some();
+
+    // Time ranges: validate against the duration reported by the metadata fetch
+    let sections = match options.sections.as_deref() {
+        Some(ranges) if !ranges.is_empty() => {
+            if is_live {
+                return Err("Time ranges can't be combined with live recording".to_string());
+            }
+            let ttl = std::time::Duration::from_secs(settings::get().metadata_cache_ttl_secs);
+            let metadata = match metadata_cache::get_video(&url, ttl) {
+                Some(cached) => cached,
+                None => {
+                    let fetched = fetch_single_video_metadata(app, &ytdlp, url.clone()).await?;
+                    metadata_cache::insert_video(&url, fetched.clone(), ttl);
+                    fetched
+                }
+            };
+            let duration = metadata.videos.first().and_then(|v| v.duration_seconds);
+            build_download_sections(ranges, duration)?
+        }
+        _ => Vec::new(),
+    };
+
+    // Each section is written to its own file, so the range goes into the name
+    let output_template = if sections.is_empty() {
+        format!("{}/%(title)s.%(ext)s", output_path)
+    } else {
+        format!("{}/%(title)s [%(section_start)s-%(section_end)s].%(ext)s", output_path)
+    };
+
     if !output_dir.exists() {
         println!("Creating output directory: {:?}", output_dir);
-        fs::create_dir_all(&output_dir)
+        fs::create_dir_all(output_dir)
             .map_err(|e| format!("Failed to create output directory: {}", e))?;
     }
 
     // Build yt-dlp command
-    let mut cmd = Command::new(&ytdlp);
-
-    // Windows: prevent console window
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(CREATE_NO_WINDOW);
-
-    // Common anti-bot detection flags
-    cmd.args([
-        "--user-agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36",
-        "--referer", "https://www.youtube.com/",
-        "--extractor-retries", "3",
-        "--no-cache-dir",
-        "--socket-timeout", "30",
-    ]);
+    let mut cmd = ytdlp_command(app, &ytdlp, &url);
 
     // Format-specific arguments and output template
     if download_type == "audio" {
         // Audio: Download best audio quality without conversion (no FFmpeg needed)
         cmd.args([
             "-f", "bestaudio/best",
-            "-o", &format!("{}/%(title)s.%(ext)s", output_path),
+            "-o", &output_template,
             "--newline",
             "--no-playlist",
         ]);
@@ -504,12 +1061,33 @@ async fn download_video(
 
         cmd.args([
             "-f", format_string,
-            "-o", &format!("{}/%(title)s.%(ext)s", output_path),
+            "-o", &output_template,
             "--newline",
             "--no-playlist",
         ]);
     }
 
+    if let Some(from_start) = live_from_start {
+        cmd.arg(if from_start { "--live-from-start" } else { "--no-live-from-start" });
+
+        // FFmpeg records HLS livestreams and writes its stats to stderr, which is only
+        // read once the process exits. Silence it so the pipe can't fill up mid-recording.
+        cmd.args(["--downloader-args", "ffmpeg:-nostats -loglevel error"]);
+    }
+
+    // Sections are cut by FFmpeg; precise cuts re-encode around the cut points
+    for section in &sections {
+        cmd.args(["--download-sections", section]);
+    }
+    if !sections.is_empty() && options.precise_cuts.unwrap_or(false) {
+        cmd.arg("--force-keyframes-at-cuts");
+    }
+
+    // Scheduled premieres/upcoming streams: poll until the video becomes available
+    if let Some(interval) = options.wait_for_video {
+        cmd.args(["--wait-for-video", &interval.max(1).to_string()]);
+    }
+
     cmd.arg(&url);
 
     println!("Spawning download process...");
@@ -531,23 +1109,25 @@ async fn download_video(
     {
         let mut registry = DOWNLOAD_REGISTRY.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
-        registry.insert(download_id.clone(), ActiveDownload::new(
-            child,
-            stdout,
-            stderr,
-            output_path.clone(),
-            title.clone(),
-        ));
+
+        // A retry takes over the failed attempt's entry, unless it was cancelled in the meantime
+        if job.retried && registry.get(&download_id).is_none_or(|d| d.cancelled) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("Download was cancelled".to_string());
+        }
+        registry.insert(download_id.clone(), ActiveDownload::new(child, stdout, stderr, job, is_live));
     }
 
     // Emit initial state
-    let _ = app.emit("download-progress", serde_json::json!({
+    app.emit_event("download-progress", serde_json::json!({
         "id": download_id,
         "url": url,
         "title": title,
         "progress": 0.0,
         "status": "starting",
         "downloadType": download_type,
+        "live": is_live,
     }));
 
     // Start monitoring the download in a separate thread
@@ -557,7 +1137,7 @@ async fn download_video(
         monitor_download(download_id_clone, app_clone);
     });
 
-    Ok(download_id)
+    Ok(())
 }
 
 // Download entire playlist
@@ -580,11 +1160,13 @@ async fn download_playlist(
             output_path.clone(),
             video.title.clone(),
             video_quality.clone(),
+            None,
         ).await {
             Ok(id) => download_ids.push(id),
             Err(e) => {
                 let _ = app.emit("download-error", serde_json::json!({
                     "url": video.url,
+                    "category": errors::classify(&e),
                     "error": e,
                 }));
             }
@@ -595,17 +1177,17 @@ async fn download_playlist(
 }
 
 // Monitor download progress
-fn monitor_download(download_id: String, app: AppHandle) {
+fn monitor_download(download_id: String, app: impl Host) {
     use std::io::{BufRead, BufReader};
 
     // Take stdout from registry for reading
-    let reader = {
+    let (reader, live) = {
         let mut registry = DOWNLOAD_REGISTRY.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e)).unwrap();
         if let Some(download) = registry.get_mut(&download_id) {
-            download.stdout.take()
+            (download.stdout.take(), download.live)
         } else {
-            None
+            (None, false)
         }
     };
 
@@ -615,7 +1197,7 @@ fn monitor_download(download_id: String, app: AppHandle) {
     std::thread::spawn(move || {
         if let Some(stdout) = reader {
             let reader = BufReader::new(stdout).lines();
-            for line in reader.flatten() {
+            for line in reader.map_while(Result::ok) {
                 // Check if download was cancelled before processing this line
                 let is_cancelled = {
                     let registry = DOWNLOAD_REGISTRY.lock().unwrap();
@@ -627,6 +1209,25 @@ fn monitor_download(download_id: String, app: AppHandle) {
                     break;
                 }
 
+                // The exact file names, as yt-dlp sanitizes titles before using them
+                // Format: [download] Destination: /path/Title.mp4
+                if let Some(destination) = line.strip_prefix("[download] Destination: ") {
+                    if let Ok(mut registry) = DOWNLOAD_REGISTRY.lock() {
+                        if let Some(download) = registry.get_mut(&download_id_clone) {
+                            let file = PathBuf::from(destination.trim());
+                            if !download.files.contains(&file) {
+                                download.files.push(file);
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                // Live recordings have no known total size, so percentages are meaningless
+                if live {
+                    continue;
+                }
+
                 // Parse yt-dlp progress output
                 // Format: [download]  45.2% of 10.00MiB at  1.00MiB/s ETA 00:05
                 if line.contains("[download]") && line.contains('%') {
@@ -636,7 +1237,7 @@ fn monitor_download(download_id: String, app: AppHandle) {
                             if let Some(percent_end) = percent_str.find('%') {
                                 let percent_num = &percent_str[..percent_end];
                                 if let Ok(progress) = percent_num.trim().parse::<f64>() {
-                                    let _ = app_clone.emit("download-progress", serde_json::json!({
+                                    app_clone.emit_event("download-progress", serde_json::json!({
                                         "id": download_id_clone,
                                         "status": "downloading",
                                         "progress": progress,
@@ -650,10 +1251,14 @@ fn monitor_download(download_id: String, app: AppHandle) {
         }
     });
 
+    // Live recordings: elapsed time is measured from the first recorded bytes
+    let mut recording_started: Option<std::time::Instant> = None;
+    let mut last_live_report = std::time::Instant::now();
+
     // Keep checking the process
     loop {
         // Check if download was cancelled
-        let child_result = {
+        let (child_result, stopping) = {
             let mut registry = match DOWNLOAD_REGISTRY.lock() {
                 Ok(r) => r,
                 Err(_) => {
@@ -670,7 +1275,8 @@ fn monitor_download(download_id: String, app: AppHandle) {
                 return; // Exit monitoring thread immediately
             }
 
-            registry.get_mut(&download_id).map(|d| d.child.try_wait())
+            let stopping = download.map(|d| d.stopping).unwrap_or(false);
+            (registry.get_mut(&download_id).map(|d| d.child.try_wait()), stopping)
         };
 
         match child_result {
@@ -679,8 +1285,17 @@ fn monitor_download(download_id: String, app: AppHandle) {
                 println!("Download process exited for: {}", download_id);
                 println!("Exit code: {:?}", result.code());
 
-                // Check if it was an error
-                if result.code() != Some(0) {
+                // A stopped recording exits non-zero after yt-dlp finalizes the file
+                if stopping {
+                    app.emit_event("download-progress", serde_json::json!({
+                        "id": download_id,
+                        "progress": 100.0,
+                        "status": "completed",
+                        "live": true,
+                        "elapsed": recording_started.map(|s| s.elapsed().as_secs()).unwrap_or(0),
+                        "recordedBytes": recorded_bytes(&download_id),
+                    }));
+                } else if result.code() != Some(0) {
                     // Try to capture stderr from the child process
                     // IMPORTANT: Take stderr OUTSIDE the lock to avoid holding lock during I/O
                     let stderr_option = {
@@ -730,13 +1345,45 @@ fn monitor_download(download_id: String, app: AppHandle) {
                     };
 
                     eprintln!("Error details: {}", error_msg);
-                    let _ = app.emit("download-progress", serde_json::json!({
+
+                    // An outdated yt-dlp is the usual cause of bot checks and broken extractors
+                    let category = errors::classify(&error_msg);
+                    // The entry stays registered while yt-dlp is updated, so the download can still be cancelled
+                    let job = {
+                        let registry = DOWNLOAD_REGISTRY.lock()
+                            .map_err(|_| "Failed to acquire lock").unwrap();
+                        registry.get(&download_id).map(|download| download.job.clone())
+                    };
+                    if let Some(job) = job.filter(|job| !job.retried) {
+                        if auto_update::update_after_failure(&app, &download_id, category, job.started_at) {
+                            let retry = DownloadJob { retried: true, ..job };
+                            match tauri::async_runtime::block_on(start_download_job(&app, download_id.clone(), retry)) {
+                                Ok(()) => {
+                                    println!("Retrying download {} with the updated yt-dlp", download_id);
+                                    break;
+                                }
+                                Err(e) => eprintln!("Failed to retry download {}: {}", download_id, e),
+                            }
+                        }
+                    }
+
+                    // Cancelled during the update: cancel_download already reported it
+                    let cancelled = DOWNLOAD_REGISTRY.lock()
+                        .map(|registry| registry.get(&download_id).is_none_or(|d| d.cancelled))
+                        .unwrap_or(false);
+                    if cancelled {
+                        println!("Download {} was cancelled, stopping monitoring", download_id);
+                        return;
+                    }
+
+                    app.emit_event("download-progress", serde_json::json!({
                         "id": download_id,
                         "status": "error",
+                        "category": category,
                         "error": error_msg,
                     }));
                 } else {
-                    let _ = app.emit("download-progress", serde_json::json!({
+                    app.emit_event("download-progress", serde_json::json!({
                         "id": download_id,
                         "progress": 100.0,
                         "status": "completed",
@@ -752,12 +1399,29 @@ fn monitor_download(download_id: String, app: AppHandle) {
                 break;
             }
             Some(Ok(None)) => {
-                // Still running, just wait
+                // Still running: live recordings report elapsed duration and recorded bytes
+                if live && last_live_report.elapsed() >= std::time::Duration::from_secs(1) {
+                    last_live_report = std::time::Instant::now();
+                    let bytes = recorded_bytes(&download_id);
+                    if bytes > 0 && recording_started.is_none() {
+                        recording_started = Some(std::time::Instant::now());
+                    }
+
+                    // No bytes yet means yt-dlp is still waiting for the stream/premiere to start
+                    app.emit_event("download-progress", serde_json::json!({
+                        "id": download_id,
+                        "status": if stopping { "stopping" } else if bytes > 0 { "recording" } else { "waiting" },
+                        "live": true,
+                        "elapsed": recording_started.map(|s| s.elapsed().as_secs()).unwrap_or(0),
+                        "recordedBytes": bytes,
+                    }));
+                }
+
                 std::thread::sleep(std::time::Duration::from_millis(500));
             }
             Some(Err(_)) => {
                 // Error checking process status
-                let _ = app.emit("download-progress", serde_json::json!({
+                app.emit_event("download-progress", serde_json::json!({
                     "id": download_id,
                     "status": "error",
                     "error": "Failed to check process status",
@@ -772,6 +1436,184 @@ fn monitor_download(download_id: String, app: AppHandle) {
     }
 }
 
+// Total size of the files a live recording has written so far: the destinations yt-dlp
+// announced, plus their .part and fragment files while they are still being written
+fn recorded_bytes(download_id: &str) -> u64 {
+    let files = DOWNLOAD_REGISTRY.lock().ok()
+        .and_then(|registry| registry.get(download_id).map(|d| d.files.clone()))
+        .unwrap_or_default();
+
+    files.iter()
+        .filter_map(|file| Some((file.parent()?, file.file_name()?.to_string_lossy().to_string())))
+        .flat_map(|(dir, name)| {
+            dir.read_dir().into_iter().flatten().flatten()
+                .filter(move |entry| entry.file_name().to_string_lossy().starts_with(&name))
+        })
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+// Stop a live recording and let yt-dlp finalize what has been recorded so far
+#[tauri::command]
+async fn stop_recording(download_id: String) -> Result<bool, String> {
+    println!("Attempting to stop recording: {}", download_id);
+
+    let pid = {
+        let mut registry = DOWNLOAD_REGISTRY.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+        let download = registry.get_mut(&download_id)
+            .ok_or_else(|| "Download not found".to_string())?;
+
+        if !download.live {
+            return Err("Download is not a live recording".to_string());
+        }
+
+        download.stopping = true;
+        download.child.id()
+    };
+
+    // yt-dlp treats an interrupt during a live download as "stop and finalize":
+    // FFmpeg gets asked to quit cleanly and fragment downloads are closed off
+    if let Err(e) = interrupt_process(pid) {
+        println!("Failed to send stop signal: {}", e);
+        if let Ok(mut registry) = DOWNLOAD_REGISTRY.lock() {
+            if let Some(download) = registry.get_mut(&download_id) {
+                download.stopping = false;
+            }
+        }
+        return Err(format!("Failed to stop recording: {}", e));
+    }
+    println!("Sent stop signal to recording PID: {}", pid);
+
+    // If yt-dlp doesn't exit on its own, terminate it but keep the recorded files
+    std::thread::spawn(move || {
+        for _ in 0..60 {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            let still_running = DOWNLOAD_REGISTRY.lock()
+                .map(|registry| registry.contains_key(&download_id))
+                .unwrap_or(false);
+            if !still_running {
+                return;
+            }
+        }
+
+        println!("Recording {} did not stop in time, terminating", download_id);
+        kill_process_tree(pid);
+        if let Ok(mut registry) = DOWNLOAD_REGISTRY.lock() {
+            if let Some(download) = registry.get_mut(&download_id) {
+                let _ = download.child.kill();
+            }
+        }
+    });
+
+    Ok(true)
+}
+
+/// Ask a process to stop the way Ctrl+C in its console would
+#[cfg(not(target_os = "windows"))]
+fn interrupt_process(pid: u32) -> Result<(), String> {
+    let output = Command::new("kill")
+        .args(["-INT", &pid.to_string()])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Ask a process to stop the way Ctrl+C in its console would
+/// yt-dlp runs in its own hidden console (CREATE_NO_WINDOW); window messages such as
+/// taskkill without /F never reach it, a console Ctrl+C does, including FFmpeg's.
+/// Sending one means attaching to that console, so a copy of this executable does it
+/// (see `run_interrupt_helper`) and the app or CLI keeps its own console and Ctrl+C handling
+#[cfg(target_os = "windows")]
+fn interrupt_process(pid: u32) -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("couldn't find own executable: {}", e))?;
+
+    let output = Command::new(exe)
+        .creation_flags(CREATE_NO_WINDOW)
+        .args([INTERRUPT_HELPER_ARG, &pid.to_string()])
+        .output()
+        .map_err(|e| e.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Hidden argument that turns the app or CLI executable into the Ctrl+C sender
+#[cfg(target_os = "windows")]
+const INTERRUPT_HELPER_ARG: &str = "--interrupt-process";
+
+/// Handle `<exe> --interrupt-process <pid>`, returns the exit code if these were the arguments
+#[cfg(target_os = "windows")]
+fn run_interrupt_helper(args: &[String]) -> Option<i32> {
+    use windows_sys::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler, CTRL_C_EVENT,
+    };
+
+    let [arg, pid] = args else { return None };
+    if arg != INTERRUPT_HELPER_ARG {
+        return None;
+    }
+    let Ok(pid) = pid.parse::<u32>() else {
+        eprintln!("invalid process id: {}", pid);
+        return Some(1);
+    };
+
+    // SAFETY: plain Win32 console calls without pointers. This short-lived process ignores
+    // Ctrl+C while attached, so the event it sends to the whole console can't end it
+    unsafe {
+        FreeConsole();
+        if AttachConsole(pid) == 0 {
+            eprintln!("couldn't attach to the console of process {}: {}", pid, std::io::Error::last_os_error());
+            return Some(1);
+        }
+        SetConsoleCtrlHandler(None, 1);
+        let sent = GenerateConsoleCtrlEvent(CTRL_C_EVENT, 0);
+        let error = std::io::Error::last_os_error();
+
+        // Events are delivered asynchronously; once detached none can reach this process anymore
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        FreeConsole();
+        SetConsoleCtrlHandler(None, 0);
+
+        if sent == 0 {
+            eprintln!("couldn't send Ctrl+C to process {}: {}", pid, error);
+            return Some(1);
+        }
+    }
+    Some(0)
+}
+
+/// Forcefully end a process and everything it started (FFmpeg for recordings)
+fn kill_process_tree(pid: u32) {
+    #[cfg(target_os = "windows")]
+    let result = {
+        let mut cmd = Command::new("taskkill");
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd.args(["/F", "/T", "/PID", &pid.to_string()]).output()
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let result = Command::new("pkill")
+        .args(["-KILL", "-P", &pid.to_string()])
+        .output();
+
+    if let Err(e) = result {
+        println!("Failed to terminate process tree {}: {}", pid, e);
+    }
+}
+
 // Cancel download
 #[tauri::command]
 async fn cancel_download(app: AppHandle, download_id: String) -> Result<bool, String> {
@@ -954,6 +1796,15 @@ fn cleanup_partial_files(output_path: &str, title: &str) -> Result<(), String> {
 // Get download directory
 #[tauri::command]
 async fn select_download_folder(app: AppHandle) -> Result<String, String> {
+    default_download_folder(&app)
+}
+
+/// Configured output folder, or the user's Downloads folder
+fn default_download_folder(app: &impl Host) -> Result<String, String> {
+    if let Some(path) = settings::get().default_output_path.filter(|p| !p.is_empty()) {
+        return Ok(path);
+    }
+
     // Try to get the user's home directory (works on all platforms)
     if let Some(home) = std::env::var("HOME").ok()
         .or_else(|| std::env::var("USERPROFILE").ok()) {
@@ -962,63 +1813,271 @@ async fn select_download_folder(app: AppHandle) -> Result<String, String> {
         Ok(downloads.to_string_lossy().to_string())
     } else {
         // Fallback to app local data directory
-        let local_dir = app.path().app_local_data_dir()
-            .map_err(|e| format!("Failed to get local data dir: {}", e))?;
+        let local_dir = app.local_data_dir()?;
         Ok(local_dir.to_string_lossy().to_string())
     }
 }
 
+/// Fetch metadata for a URL and start downloading everything in it
+/// Used for downloads that don't come from the main window (clipboard, links, ...),
+/// type and quality fall back to the configured defaults
+async fn queue_url(
+    app: AppHandle,
+    url: String,
+    download_type: Option<String>,
+    video_quality: Option<String>,
+) -> Result<Vec<String>, String> {
+    let settings = settings::get();
+    let output_path = default_download_folder(&app)?;
+    let metadata = fetch_metadata(app.clone(), url.clone(), None, None).await?;
+
+    download_playlist(
+        app,
+        url,
+        download_type.unwrap_or(settings.default_download_type),
+        output_path,
+        metadata.videos,
+        video_quality.unwrap_or(settings.default_video_quality),
+    ).await
+}
+
 // Update yt-dlp to latest version
 #[tauri::command]
 async fn update_ytdlp(app: AppHandle) -> Result<String, String> {
-    let ytdlp = get_ytdlp_path(&app)?;
+    update_ytdlp_binary(&app)
+}
 
-    println!("Updating yt-dlp at: {:?}", ytdlp);
+fn update_ytdlp_binary(app: &impl Host) -> Result<String, String> {
+    let (mut ytdlp, source) = resolve_ytdlp(app)?;
+    if matches!(source, YtdlpSource::Path | YtdlpSource::Custom) {
+        return Err(format!(
+            "yt-dlp at {} is not managed by Verdl. Update it the way it was installed (package manager, pipx, ...).",
+            ytdlp.display()
+        ));
+    }
 
-    let mut cmd = Command::new(&ytdlp);
+    let local_data_dir = app.local_data_dir()?;
 
-    // Windows: prevent console window
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(CREATE_NO_WINDOW);
+    // The app bundle may be read-only, a bundled yt-dlp is updated as a copy in local data
+    if source == YtdlpSource::Bundled {
+        let local_ytdlp = local_data_dir.join(ytdlp_binary_name());
+        fs::create_dir_all(&local_data_dir)
+            .and_then(|_| fs::copy(&ytdlp, &local_ytdlp))
+            .map_err(|e| format!("Failed to copy bundled yt-dlp: {}", e))?;
+        ytdlp = local_ytdlp;
+    }
 
-    let output = cmd
-        .arg("--update")
-        .output()
-        .map_err(|e| format!("Failed to run yt-dlp update: {}", e))?;
+    // Channel and pinned version from settings; a pin also allows going back to an older release.
+    // yt-dlp's self-update only knows GitHub, with a mirror the release is installed from there
+    let settings = settings::get();
+    let result = update::safe_update(app, &ytdlp, &local_data_dir, || match install::mirror(&settings) {
+        Some(_) => install::download_ytdlp(app, &install::download_url(&settings), &local_data_dir)
+            .map(|_| String::new()),
+        None => update::self_update(&ytdlp, &install::update_target(&settings)),
+    });
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Pick up the managed copy, and a new release may add or rename extractors
+    reset_ytdlp_path();
+    result
+}
 
-    if output.status.success() {
-        Ok(format!("yt-dlp updated successfully.\n{}", stdout))
-    } else {
-        Err(format!("Failed to update yt-dlp: {}", stderr))
+/// Version reported by `yt-dlp --version`
+fn installed_ytdlp_version(ytdlp: &Path) -> Option<String> {
+    let output = local_ytdlp_command(ytdlp).arg("--version").output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !version.is_empty()).then_some(version)
+}
+
+// Installed yt-dlp version and the latest one on the configured channel
+#[tauri::command]
+async fn get_ytdlp_version(app: AppHandle) -> Result<install::VersionReport, String> {
+    let installed = get_ytdlp_path(&app).ok().and_then(|path| installed_ytdlp_version(&path));
+    Ok(install::version_report(installed, &settings::get()))
+}
+
+// yt-dlp versions kept for rollback, newest first
+#[tauri::command]
+async fn list_ytdlp_versions(app: AppHandle) -> Result<Vec<update::SavedVersion>, String> {
+    let installed = get_ytdlp_path(&app).ok().and_then(|path| installed_ytdlp_version(&path));
+    Ok(update::list_versions(&app.local_data_dir()?, installed.as_deref()))
+}
+
+// Go back to a previously installed yt-dlp version
+#[tauri::command]
+async fn rollback_ytdlp(app: AppHandle, version: String) -> Result<String, String> {
+    rollback_ytdlp_binary(&app, &version)
+}
+
+fn rollback_ytdlp_binary(app: &impl Host, version: &str) -> Result<String, String> {
+    let (ytdlp, source) = resolve_ytdlp(app)?;
+    if matches!(source, YtdlpSource::Path | YtdlpSource::Custom) {
+        return Err(format!("yt-dlp at {} is not managed by Verdl and can't be rolled back.", ytdlp.display()));
     }
+
+    // A bundled yt-dlp stays untouched, the restored version goes to local data and takes precedence
+    let local_data_dir = app.local_data_dir()?;
+    let result = update::rollback(&local_data_dir.join(ytdlp_binary_name()), version, &local_data_dir);
+    reset_ytdlp_path();
+    let message = result?;
+
+    // Pin the restored version, or the next automatic update would replace it again
+    let mut settings = settings::get();
+    settings.ytdlp_version = Some(version.to_string());
+    settings::save(app, settings)?;
+    println!("Pinned yt-dlp to {} after rollback", version);
+
+    Ok(format!(
+        "{} Pinned yt-dlp to {} so automatic updates keep it; clear the pinned version in settings to update again.",
+        message, version
+    ))
+}
+
+// Get backend settings
+#[tauri::command]
+async fn get_settings() -> Result<Settings, String> {
+    Ok(settings::get())
+}
+
+// Save backend settings
+#[tauri::command]
+async fn update_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
+    let previous = settings::get();
+    let source_changed = previous.ytdlp_source != settings.ytdlp_source
+        || previous.ytdlp_custom_path != settings.ytdlp_custom_path;
+    let runtime_changed = previous.js_runtime != settings.js_runtime
+        || previous.js_runtime_download != settings.js_runtime_download;
+
+    // A yt-dlp from PATH or a custom file has to work before it is switched to
+    if source_changed {
+        match settings.ytdlp_source.as_str() {
+            "path" => {
+                let path = find_on_path(ytdlp_binary_name())
+                    .ok_or_else(|| "Failed to execute yt-dlp: not found on PATH".to_string())?;
+                validate_ytdlp(&path)?;
+            }
+            "custom" => {
+                validate_ytdlp(Path::new(settings.ytdlp_custom_path.as_deref().unwrap_or_default()))?;
+            }
+            _ => {}
+        }
+    }
+
+    settings::save(&app, settings)?;
+    if source_changed {
+        reset_ytdlp_path();
+    } else if runtime_changed {
+        js_runtime::reset();
+        js_runtime::install_in_background(app.clone());
+    }
+    api::apply(&app)
+}
+
+// Running downloads
+#[tauri::command]
+async fn get_download_queue() -> Result<Vec<history::DownloadEntry>, String> {
+    Ok(history::active())
+}
+
+// Finished downloads, newest first
+#[tauri::command]
+async fn get_download_history() -> Result<Vec<history::DownloadEntry>, String> {
+    Ok(history::history())
+}
+
+#[tauri::command]
+async fn clear_download_history() -> Result<(), String> {
+    history::clear();
+    Ok(())
 }
 
 // Validate URL
 #[tauri::command]
-async fn validate_url(url: String) -> Result<bool, String> {
+async fn validate_url(app: AppHandle, url: String) -> Result<bool, String> {
     if url.is_empty() {
         return Ok(false);
     }
 
     // Use strict URL validation to prevent bypass attempts
-    Ok(is_valid_youtube_url(&url))
+    Ok(parse_supported_url(&app, &url).is_ok())
+}
+
+// Classify a URL (video, short, live, playlist, channel, ...) and extract its ids
+#[tauri::command]
+async fn classify_url(app: AppHandle, url: String) -> Result<urls::ParsedUrl, String> {
+    parse_supported_url(&app, &url)
+}
+
+// Pause or resume the clipboard watcher (it only runs when enabled in settings)
+#[tauri::command]
+async fn set_clipboard_watch_paused(paused: bool) -> Result<bool, String> {
+    clipboard::set_paused(paused);
+    Ok(paused)
+}
+
+// Extract every supported URL from pasted text or a text file, deduplicated and classified
+#[tauri::command]
+async fn import_urls(app: AppHandle, text: Option<String>, file_path: Option<String>) -> Result<import::ImportResult, String> {
+    let text = match (text, file_path) {
+        (Some(text), _) => text,
+        (None, Some(path)) => import::read_file(Path::new(&path))?,
+        (None, None) => return Err("Nothing to import: provide text or a file".to_string()),
+    };
+
+    let allowed_sites = settings::get().allowed_sites;
+    let result = import::extract_urls(&text, &allowed_sites, |url| parse_supported_url(&app, url).ok());
+    println!(
+        "import_urls: {} URLs, {} duplicates, {} unrecognized lines",
+        result.urls.len(),
+        result.duplicates,
+        result.unrecognized.len()
+    );
+    Ok(result)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    #[cfg(target_os = "windows")]
+    if let Some(code) = run_interrupt_helper(&std::env::args().skip(1).collect::<Vec<_>>()) {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
+        // Must come first: a second launch hands its arguments (and verdl:// link) over and exits
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            deep_link::focus_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .register_asynchronous_uri_scheme_protocol(thumbnails::SCHEME, |ctx, request, responder| {
+            // Thumbnails may need downloading, keep that off the webview's thread
+            let app = ctx.app_handle().clone();
+            std::thread::spawn(move || {
+                responder.respond(thumbnails::handle_request(&app, request));
+            });
+        })
         .setup(|app| {
+            if let Err(e) = settings::load(app.handle()) {
+                eprintln!("Failed to load settings: {}", e);
+            }
+
+            history::init(app.handle());
+            clipboard::start(app.handle().clone());
+            deep_link::setup(app.handle());
+            if let Err(e) = api::apply(app.handle()) {
+                eprintln!("{}", e);
+            }
+            auto_update::start(app.handle().clone());
+
             // Initialize yt-dlp on app startup
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 if let Err(e) = get_ytdlp_path(&app_handle) {
                     eprintln!("Failed to initialize yt-dlp: {}", e);
                 }
+                // Also download the JavaScript runtime, if allowed, before the first download needs it
+                js_runtime::install_in_background(app_handle);
             });
             Ok(())
         })
@@ -1028,10 +2087,112 @@ pub fn run() {
             download_video,
             download_playlist,
             cancel_download,
+            cancel_metadata_fetch,
+            stop_recording,
             select_download_folder,
             validate_url,
+            classify_url,
+            import_urls,
+            set_clipboard_watch_paused,
             update_ytdlp,
+            get_ytdlp_version,
+            list_ytdlp_versions,
+            rollback_ytdlp,
+            get_settings,
+            update_settings,
+            get_download_queue,
+            get_download_history,
+            clear_download_history,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: &str, end: Option<&str>) -> TimeRange {
+        TimeRange { start: start.to_string(), end: end.map(|e| e.to_string()) }
+    }
+
+    #[test]
+    fn parses_timestamps() {
+        let cases = [
+            ("0", 0.0),
+            ("42", 42.0),
+            ("90", 90.0),
+            ("12.5", 12.5),
+            ("1:05", 65.0),
+            ("01:05.25", 65.25),
+            ("59:59", 3599.0),
+            ("1:02:03", 3723.0),
+            ("10:00:00", 36000.0),
+            (" 2:30 ", 150.0),
+        ];
+
+        for (timestamp, seconds) in cases {
+            assert_eq!(parse_timestamp(timestamp), Ok(seconds), "seconds for {:?}", timestamp);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        let cases = ["", "abc", "-5", "1:60", "1:00:60", "1.5:00", "1:2:3:4", "1::2", "inf", "NaN"];
+
+        for timestamp in cases {
+            assert!(parse_timestamp(timestamp).is_err(), "expected {:?} to be rejected", timestamp);
+        }
+    }
+
+    #[test]
+    fn builds_download_sections() {
+        let sections = [range("0:30", Some("1:00")), range("1:00:00", None), range("5", Some("7.5"))];
+        assert_eq!(
+            build_download_sections(&sections, None),
+            Ok(vec!["*30-60".to_string(), "*3600-inf".to_string(), "*5-7.5".to_string()])
+        );
+        assert_eq!(build_download_sections(&[], None), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn rejects_ranges_that_end_before_they_start() {
+        assert!(build_download_sections(&[range("1:00", Some("0:30"))], None).is_err());
+        assert!(build_download_sections(&[range("1:00", Some("60"))], None).is_err());
+        // One bad range fails the whole request
+        assert!(build_download_sections(&[range("0", Some("10")), range("20", Some("15"))], None).is_err());
+    }
+
+    #[test]
+    fn skips_nested_collections() {
+        let tab = serde_json::json!({"_type": "url", "ie_key": "YoutubeTab", "url": "https://www.youtube.com/@LinusTechTips/shorts"});
+        assert!(is_nested_collection(&tab, "https://www.youtube.com/@LinusTechTips/shorts"));
+
+        let playlist = serde_json::json!({"_type": "url", "ie_key": "SoundcloudPlaylist"});
+        assert!(is_nested_collection(&playlist, "https://soundcloud.com/artist/sets/album"));
+
+        let video = serde_json::json!({"_type": "url", "ie_key": "Youtube", "id": "dQw4w9WgXcQ"});
+        assert!(!is_nested_collection(&video, "https://www.youtube.com/watch?v=dQw4w9WgXcQ"));
+
+        let other = serde_json::json!({"_type": "url", "ie_key": "Vimeo"});
+        assert!(!is_nested_collection(&other, "https://vimeo.com/123456"));
+    }
+
+    #[test]
+    fn reads_extractor_name() {
+        assert_eq!(extractor_name("[vimeo] Extracting URL: https://vimeo.com/123456"), Some("vimeo".to_string()));
+        assert_eq!(extractor_name("[BBC] Extracting URL: https://www.bbc.co.uk/news/av/1"), Some("bbc".to_string()));
+        assert_eq!(extractor_name("[generic] Extracting URL: https://example.com/"), Some("generic".to_string()));
+        assert_eq!(extractor_name("[vimeo] 123456: Downloading webpage"), None);
+        assert_eq!(extractor_name("WARNING: [generic] Falling back on generic information extractor"), None);
+    }
+
+    #[test]
+    fn checks_ranges_against_duration() {
+        assert!(build_download_sections(&[range("0", Some("2:00"))], Some(120.0)).is_ok());
+        assert!(build_download_sections(&[range("1:00", None)], Some(120.0)).is_ok());
+        assert!(build_download_sections(&[range("0", Some("2:01"))], Some(120.0)).is_err());
+        assert!(build_download_sections(&[range("2:00", None)], Some(120.0)).is_err());
+        assert!(build_download_sections(&[range("3:00", Some("4:00"))], Some(120.0)).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+// In-memory cache for fetch_metadata results
+// Re-opening the same video or playlist shouldn't spawn yt-dlp again: it's slow
+// and every extra request counts towards YouTube's bot detection
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use crate::{urls, PlaylistInfo};
+
+/// Entries kept at most, the oldest is dropped when full
+const MAX_ENTRIES: usize = 200;
+
+static CACHE: Lazy<Mutex<HashMap<String, (Instant, PlaylistInfo)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Cache key for a URL: the video or playlist id for YouTube links, otherwise the canonical URL
+/// so `youtu.be/ID`, `youtube.com/watch?v=ID&t=30` and friends share one entry
+pub fn cache_key(url: &str) -> String {
+    urls::parse(url.trim())
+        .map(|parsed| parsed.dedup_key())
+        .unwrap_or_else(|| url.trim().to_string())
+}
+
+/// Cache key for the single video a URL points to, even when it also names a playlist
+/// (`watch?v=ID&list=PL...`), so the video's info never lands on the playlist's entry
+pub fn video_cache_key(url: &str) -> String {
+    match urls::parse(url.trim()) {
+        Some(urls::ParsedUrl { video_id: Some(video_id), .. }) => format!("video:{}", video_id),
+        _ => cache_key(url),
+    }
+}
+
+/// Cached metadata for a URL if it is younger than `ttl`
+pub fn get(url: &str, ttl: Duration) -> Option<PlaylistInfo> {
+    get_by_key(&cache_key(url), ttl)
+}
+
+/// Cached single-video metadata for a URL, see video_cache_key
+pub fn get_video(url: &str, ttl: Duration) -> Option<PlaylistInfo> {
+    get_by_key(&video_cache_key(url), ttl)
+}
+
+fn get_by_key(key: &str, ttl: Duration) -> Option<PlaylistInfo> {
+    if ttl.is_zero() {
+        return None;
+    }
+
+    let cache = CACHE.lock().ok()?;
+    cache.get(key)
+        .filter(|(fetched_at, _)| fetched_at.elapsed() < ttl)
+        .map(|(_, info)| info.clone())
+}
+
+/// Store freshly fetched metadata, dropping expired and excess entries
+pub fn insert(url: &str, info: PlaylistInfo, ttl: Duration) {
+    insert_by_key(cache_key(url), info, ttl);
+}
+
+/// Store freshly fetched single-video metadata, see video_cache_key
+pub fn insert_video(url: &str, info: PlaylistInfo, ttl: Duration) {
+    insert_by_key(video_cache_key(url), info, ttl);
+}
+
+fn insert_by_key(key: String, info: PlaylistInfo, ttl: Duration) {
+    if ttl.is_zero() {
+        return;
+    }
+
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ttl);
+
+        if cache.len() >= MAX_ENTRIES {
+            let oldest = cache.iter()
+                .min_by_key(|(_, (fetched_at, _))| *fetched_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+
+        cache.insert(key, (Instant::now(), info));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn info(title: &str) -> PlaylistInfo {
+        PlaylistInfo {
+            id: None,
+            title: title.to_string(),
+            uploader: None,
+            description: None,
+            thumbnail: None,
+            video_count: 0,
+            total_count: None,
+            videos: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn video_in_playlist_keeps_its_own_entry() {
+        let playlist = "https://www.youtube.com/playlist?list=PLcacheTestPlaylist000000000000000";
+        let video = "https://www.youtube.com/watch?v=cacheTest01&list=PLcacheTestPlaylist000000000000000";
+
+        insert(playlist, info("playlist"), TTL);
+        insert_video(video, info("video"), TTL);
+
+        assert_eq!(get(playlist, TTL).map(|i| i.title).as_deref(), Some("playlist"));
+        assert_eq!(get(video, TTL).map(|i| i.title).as_deref(), Some("playlist"));
+        assert_eq!(get_video(video, TTL).map(|i| i.title).as_deref(), Some("video"));
+        // The plain video URL shares the entry
+        assert_eq!(get("https://youtu.be/cacheTest01", TTL).map(|i| i.title).as_deref(), Some("video"));
+    }
+
+    #[test]
+    fn video_cache_key_falls_back_to_cache_key() {
+        let playlist = "https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf";
+        assert_eq!(video_cache_key(playlist), cache_key(playlist));
+        assert_eq!(video_cache_key("https://vimeo.com/123456"), "https://vimeo.com/123456");
+    }
+}
@@ -0,0 +1,250 @@
+// Network identity for every yt-dlp request: user agent, headers, retries, timeouts and
+// which local address/IP version to connect from. Metadata fetches and downloads used to
+// each carry their own (differing) copy of these flags
+
+use std::net::IpAddr;
+use serde::{Deserialize, Serialize};
+use crate::urls;
+
+const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestProfile {
+    pub user_agent: String,
+    /// Extra HTTP headers as "Name: value"; a Referer here replaces the site's front page
+    pub headers: Vec<String>,
+    /// Retries when extraction fails (--extractor-retries)
+    pub extractor_retries: u32,
+    /// Retries of a download and of each fragment (--retries, --fragment-retries)
+    pub retries: u32,
+    pub socket_timeout_secs: u32,
+    /// "any", "ipv4" or "ipv6"
+    pub ip_version: String,
+    /// Local IP address to connect from
+    pub source_address: Option<String>,
+}
+
+impl Default for RequestProfile {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            headers: Vec::new(),
+            extractor_retries: 3,
+            retries: 10,
+            socket_timeout_secs: 30,
+            ip_version: "any".to_string(),
+            source_address: None,
+        }
+    }
+}
+
+/// Referer header for yt-dlp requests: the site's own front page
+fn referer_for(url: &str) -> String {
+    match urls::host(url) {
+        Some(host) if !urls::is_youtube_host(&host) => {
+            format!("https://{}/", host)
+        }
+        _ => "https://www.youtube.com/".to_string(),
+    }
+}
+
+fn source_address(profile: &RequestProfile) -> Option<&str> {
+    profile.source_address.as_deref().filter(|address| !address.is_empty())
+}
+
+impl RequestProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.user_agent.trim().is_empty() || self.user_agent.contains(['\r', '\n']) {
+            return Err("Invalid user agent: it can't be empty or span several lines".to_string());
+        }
+        for header in &self.headers {
+            let valid = header.split_once(':').is_some_and(|(name, value)| {
+                !name.trim().is_empty()
+                    && name.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    && !value.contains(['\r', '\n'])
+            });
+            if !valid {
+                return Err(format!("Invalid header '{}'. Expected 'Name: value'.", header));
+            }
+        }
+        if self.socket_timeout_secs == 0 {
+            return Err("Invalid socket timeout: use at least 1 second".to_string());
+        }
+        if !["any", "ipv4", "ipv6"].contains(&self.ip_version.as_str()) {
+            return Err(format!("Invalid IP version '{}'. Expected 'any', 'ipv4' or 'ipv6'.", self.ip_version));
+        }
+        if let Some(address) = source_address(self) {
+            let ip = address.parse::<IpAddr>()
+                .map_err(|_| format!("Invalid source address '{}'. Expected an IP address.", address))?;
+            if (self.ip_version == "ipv4" && ip.is_ipv6()) || (self.ip_version == "ipv6" && ip.is_ipv4()) {
+                return Err(format!("Invalid source address '{}': it doesn't match the IP version", address));
+            }
+        }
+        Ok(())
+    }
+
+    /// yt-dlp arguments for a request to `url`
+    pub fn args(&self, url: &str) -> Vec<String> {
+        let mut args = vec![
+            "--user-agent".to_string(), self.user_agent.clone(),
+            "--extractor-retries".to_string(), self.extractor_retries.to_string(),
+            "--retries".to_string(), self.retries.to_string(),
+            "--fragment-retries".to_string(), self.retries.to_string(),
+            "--socket-timeout".to_string(), self.socket_timeout_secs.to_string(),
+            "--no-cache-dir".to_string(),
+        ];
+
+        let has_referer = self.headers.iter()
+            .any(|header| header.split(':').next().is_some_and(|name| name.trim().eq_ignore_ascii_case("referer")));
+        if !has_referer {
+            args.extend(["--referer".to_string(), referer_for(url)]);
+        }
+        for header in &self.headers {
+            let (name, value) = header.split_once(':').unwrap_or((header, ""));
+            args.extend(["--add-header".to_string(), format!("{}:{}", name.trim(), value.trim())]);
+        }
+
+        match self.ip_version.as_str() {
+            "ipv4" => args.push("--force-ipv4".to_string()),
+            "ipv6" => args.push("--force-ipv6".to_string()),
+            _ => {}
+        }
+        if let Some(address) = source_address(self) {
+            args.extend(["--source-address".to_string(), address.to_string()]);
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_headers(headers: &[&str]) -> RequestProfile {
+        RequestProfile { headers: headers.iter().map(|h| h.to_string()).collect(), ..RequestProfile::default() }
+    }
+
+    fn with_address(ip_version: &str, source_address: Option<&str>) -> RequestProfile {
+        RequestProfile {
+            ip_version: ip_version.to_string(),
+            source_address: source_address.map(String::from),
+            ..RequestProfile::default()
+        }
+    }
+
+    /// Value following `flag`, for each time it appears
+    fn values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+        args.windows(2).filter(|pair| pair[0] == flag).map(|pair| pair[1].as_str()).collect()
+    }
+
+    #[test]
+    fn default_profile_is_valid() {
+        let profile = RequestProfile::default();
+        assert_eq!(profile.validate(), Ok(()));
+
+        let args = profile.args("https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(values(&args, "--user-agent"), [DEFAULT_USER_AGENT]);
+        assert_eq!(values(&args, "--extractor-retries"), ["3"]);
+        assert_eq!(values(&args, "--retries"), ["10"]);
+        assert_eq!(values(&args, "--fragment-retries"), ["10"]);
+        assert_eq!(values(&args, "--socket-timeout"), ["30"]);
+        assert!(args.iter().any(|arg| arg == "--no-cache-dir"));
+        assert!(!args.iter().any(|arg| arg == "--force-ipv4" || arg == "--force-ipv6" || arg == "--source-address"));
+    }
+
+    #[test]
+    fn validates_headers() {
+        let valid = [&["Accept-Language: en-US"][..], &["X-Test:1"], &["Cookie: a=b; c=d", "DNT: 1"], &["X-Empty:"]];
+        for headers in valid {
+            assert_eq!(with_headers(headers).validate(), Ok(()), "expected {:?} to be valid", headers);
+        }
+
+        let invalid = [
+            "no colon",
+            ": value",
+            "Bad Name: value",
+            "X-Test: value\r\nX-Injected: 1",
+            "X-Test: value\nX-Injected: 1",
+            "Näme: value",
+        ];
+        for header in invalid {
+            assert!(with_headers(&[header]).validate().is_err(), "expected {:?} to be rejected", header);
+        }
+    }
+
+    #[test]
+    fn passes_headers_trimmed() {
+        let args = with_headers(&["  Accept-Language :  en-US  ", "X-Token:abc:def"]).args("https://vimeo.com/123456");
+        assert_eq!(values(&args, "--add-header"), ["Accept-Language:en-US", "X-Token:abc:def"]);
+    }
+
+    #[test]
+    fn referer_defaults_to_site_front_page() {
+        let args = RequestProfile::default().args("https://player.vimeo.com/video/123456");
+        assert_eq!(values(&args, "--referer"), ["https://player.vimeo.com/"]);
+
+        let args = RequestProfile::default().args("https://youtu.be/dQw4w9WgXcQ");
+        assert_eq!(values(&args, "--referer"), ["https://www.youtube.com/"]);
+    }
+
+    #[test]
+    fn referer_header_replaces_default_referer() {
+        let args = with_headers(&["referer: https://example.com/page"]).args("https://vimeo.com/123456");
+        assert!(values(&args, "--referer").is_empty());
+        assert_eq!(values(&args, "--add-header"), ["referer:https://example.com/page"]);
+    }
+
+    #[test]
+    fn validates_user_agent_and_timeout() {
+        let empty_agent = RequestProfile { user_agent: "  ".to_string(), ..RequestProfile::default() };
+        assert!(empty_agent.validate().is_err());
+
+        let multiline_agent = RequestProfile { user_agent: "Agent\nX-Injected: 1".to_string(), ..RequestProfile::default() };
+        assert!(multiline_agent.validate().is_err());
+
+        let no_timeout = RequestProfile { socket_timeout_secs: 0, ..RequestProfile::default() };
+        assert!(no_timeout.validate().is_err());
+    }
+
+    #[test]
+    fn validates_ip_version_and_source_address() {
+        let valid = [
+            ("any", None),
+            ("any", Some("")),
+            ("any", Some("192.168.1.10")),
+            ("any", Some("fe80::1")),
+            ("ipv4", Some("10.0.0.2")),
+            ("ipv6", Some("2001:db8::1")),
+        ];
+        for (ip_version, address) in valid {
+            assert_eq!(with_address(ip_version, address).validate(), Ok(()), "expected {} / {:?} to be valid", ip_version, address);
+        }
+
+        let invalid = [
+            ("ipv5", None),
+            ("IPv4", None),
+            ("any", Some("not-an-ip")),
+            ("any", Some("192.168.1.10:8080")),
+            ("any", Some("eth0")),
+            ("ipv4", Some("2001:db8::1")),
+            ("ipv6", Some("10.0.0.2")),
+        ];
+        for (ip_version, address) in invalid {
+            assert!(with_address(ip_version, address).validate().is_err(), "expected {} / {:?} to be rejected", ip_version, address);
+        }
+    }
+
+    #[test]
+    fn passes_ip_version_and_source_address() {
+        let args = with_address("ipv4", Some("10.0.0.2")).args("https://vimeo.com/123456");
+        assert!(args.iter().any(|arg| arg == "--force-ipv4"));
+        assert_eq!(values(&args, "--source-address"), ["10.0.0.2"]);
+
+        let args = with_address("ipv6", Some("")).args("https://vimeo.com/123456");
+        assert!(args.iter().any(|arg| arg == "--force-ipv6"));
+        assert!(values(&args, "--source-address").is_empty());
+    }
+}
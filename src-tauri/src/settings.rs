@@ -0,0 +1,223 @@
+// Persistent backend settings, stored as JSON in the app config dir
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::host::Host;
+use crate::request_profile::RequestProfile;
+use crate::{extractor_args, install, js_runtime, urls};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// How long fetched metadata is reused, in seconds (0 disables the cache)
+    pub metadata_cache_ttl_secs: u64,
+    /// Domains URLs may come from (subdomains included)
+    pub allowed_sites: Vec<String>,
+    /// Additionally require a matching yt-dlp extractor for non-YouTube sites, once yt-dlp
+    /// has been asked about the URL in the background
+    pub verify_extractors: bool,
+    /// Used for downloads started outside the main window (clipboard, links, ...)
+    pub default_download_type: String,
+    pub default_video_quality: String,
+    pub default_output_path: Option<String>, // None = ~/Downloads
+    /// Watch the clipboard for supported URLs
+    pub clipboard_watch: bool,
+    /// Queue copied URLs right away instead of offering them
+    pub clipboard_auto_queue: bool,
+    /// Serve the local automation API on 127.0.0.1
+    pub api_enabled: bool,
+    pub api_port: u16,
+    /// Armored GPG public key file; when set, the signature of a release's
+    /// SHA2-256SUMS must verify against it before yt-dlp is installed
+    pub ytdlp_signing_key: Option<String>,
+    /// yt-dlp release channel: "stable", "nightly" or "master"
+    pub ytdlp_channel: String,
+    /// Install and update to exactly this release (e.g. "2024.08.06") instead of the latest
+    pub ytdlp_version: Option<String>,
+    /// Which yt-dlp to use: "managed" (downloaded by Verdl), "path" or "custom"
+    pub ytdlp_source: String,
+    /// The executable used when ytdlp_source is "custom"
+    pub ytdlp_custom_path: Option<String>,
+    /// Update yt-dlp and retry when a download fails with a bot check or broken extractor
+    pub ytdlp_auto_update: bool,
+    /// Look for a newer yt-dlp after such failures at most this often
+    pub ytdlp_auto_update_interval_hours: u64,
+    /// Check for new yt-dlp releases in the background
+    pub ytdlp_update_check: bool,
+    pub ytdlp_update_check_interval_hours: u64,
+    /// What to do when a new release is found: "prompt" or "auto" (install right away)
+    pub ytdlp_update_install: String,
+    /// Release feed to check instead of GitHub, e.g. a local mirror. Either GitHub's
+    /// releases/latest API JSON or a plain text file holding the version
+    pub ytdlp_update_feed_url: Option<String>,
+    /// Server (base URL) or folder that managed binaries are downloaded from instead of
+    /// https://github.com, laid out the same way, checksum files included
+    pub download_mirror: Option<String>,
+    /// JavaScript runtime for YouTube challenges: "auto", "deno", "node", "bun" or "none"
+    pub js_runtime: String,
+    /// Download Deno when no runtime is installed
+    pub js_runtime_download: bool,
+    /// YouTube player clients to extract with (yt-dlp's default when empty)
+    pub youtube_player_clients: Vec<String>,
+    /// Fixed proof-of-origin tokens, CLIENT.CONTEXT+TOKEN
+    pub youtube_po_tokens: Vec<String>,
+    /// PO token provider to ask for tokens, e.g. a local bgutil server (http://127.0.0.1:4416)
+    pub po_token_provider_url: Option<String>,
+    /// User agent, headers, retries, timeouts and addresses for every yt-dlp request
+    pub request_profile: RequestProfile,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            metadata_cache_ttl_secs: 600,
+            allowed_sites: urls::YOUTUBE_DOMAINS.iter().map(|d| d.to_string()).collect(),
+            verify_extractors: false,
+            default_download_type: "video".to_string(),
+            default_video_quality: "1080p".to_string(),
+            default_output_path: None,
+            clipboard_watch: false,
+            clipboard_auto_queue: false,
+            api_enabled: false,
+            api_port: 9417,
+            ytdlp_signing_key: None,
+            ytdlp_channel: "stable".to_string(),
+            ytdlp_version: None,
+            ytdlp_source: "managed".to_string(),
+            ytdlp_custom_path: None,
+            ytdlp_auto_update: true,
+            ytdlp_auto_update_interval_hours: 6,
+            ytdlp_update_check: true,
+            ytdlp_update_check_interval_hours: 24,
+            ytdlp_update_install: "prompt".to_string(),
+            ytdlp_update_feed_url: None,
+            download_mirror: None,
+            js_runtime: "auto".to_string(),
+            js_runtime_download: false,
+            youtube_player_clients: Vec::new(),
+            youtube_po_tokens: Vec::new(),
+            po_token_provider_url: None,
+            request_profile: RequestProfile::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reject values that would weaken URL validation or can't work
+    fn validate(&self) -> Result<(), String> {
+        if let Some(site) = self.allowed_sites.iter().find(|site| !urls::is_valid_domain(site)) {
+            return Err(format!("Invalid site '{}'. Use a plain lowercase domain such as vimeo.com", site));
+        }
+        if !["video", "audio"].contains(&self.default_download_type.as_str()) {
+            return Err(format!("Invalid download type '{}'. Expected 'video' or 'audio'.", self.default_download_type));
+        }
+        if !install::CHANNELS.contains(&self.ytdlp_channel.as_str()) {
+            return Err(format!("Invalid yt-dlp channel '{}'. Expected one of: {}", self.ytdlp_channel, install::CHANNELS.join(", ")));
+        }
+        if let Some(version) = self.ytdlp_version.as_deref().filter(|v| !v.is_empty() && !install::is_valid_version(v)) {
+            return Err(format!("Invalid yt-dlp version '{}'. Expected a release tag such as 2024.08.06", version));
+        }
+        if !["managed", "path", "custom"].contains(&self.ytdlp_source.as_str()) {
+            return Err(format!("Invalid yt-dlp source '{}'. Expected 'managed', 'path' or 'custom'.", self.ytdlp_source));
+        }
+        if self.ytdlp_source == "custom" && self.ytdlp_custom_path.as_deref().is_none_or(str::is_empty) {
+            return Err("Invalid yt-dlp source: choose the yt-dlp executable to use".to_string());
+        }
+        if self.ytdlp_auto_update_interval_hours == 0 {
+            return Err("Invalid yt-dlp auto-update interval: use at least 1 hour".to_string());
+        }
+        if self.ytdlp_update_check_interval_hours == 0 {
+            return Err("Invalid yt-dlp update check interval: use at least 1 hour".to_string());
+        }
+        if !["prompt", "auto"].contains(&self.ytdlp_update_install.as_str()) {
+            return Err(format!("Invalid yt-dlp update mode '{}'. Expected 'prompt' or 'auto'.", self.ytdlp_update_install));
+        }
+        if let Some(url) = self.ytdlp_update_feed_url.as_deref().filter(|url| !url.is_empty()) {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err(format!("Invalid yt-dlp update feed '{}'. Expected an http(s) URL.", url));
+            }
+        }
+        if let Some(mirror) = self.download_mirror.as_deref().filter(|mirror| !mirror.is_empty()) {
+            if install::local_path(mirror).is_some_and(|path| !path.is_absolute()) {
+                return Err(format!("Invalid download mirror '{}'. Expected an http(s) URL or an absolute folder path.", mirror));
+            }
+        }
+        if !["auto", "none"].contains(&self.js_runtime.as_str()) && !js_runtime::RUNTIMES.contains(&self.js_runtime.as_str()) {
+            return Err(format!(
+                "Invalid JavaScript runtime '{}'. Expected 'auto', 'none' or one of: {}",
+                self.js_runtime, js_runtime::RUNTIMES.join(", ")
+            ));
+        }
+        if let Some(client) = self.youtube_player_clients.iter().find(|c| !extractor_args::is_valid_player_client(c)) {
+            return Err(format!("Invalid YouTube player client '{}'. Use a yt-dlp client name such as web or mweb.", client));
+        }
+        if let Some(token) = self.youtube_po_tokens.iter().find(|t| !extractor_args::is_valid_po_token(t)) {
+            return Err(format!("Invalid PO token '{}'. Expected CLIENT.CONTEXT+TOKEN, e.g. web.gvs+...", token));
+        }
+        if let Some(url) = self.po_token_provider_url.as_deref().filter(|url| !url.is_empty()) {
+            if !extractor_args::is_valid_provider_url(url) {
+                return Err(format!("Invalid PO token provider '{}'. Expected an http(s) URL.", url));
+            }
+        }
+        self.request_profile.validate()?;
+        if self.api_port < 1024 {
+            return Err(format!("Invalid API port {}. Use a port between 1024 and 65535.", self.api_port));
+        }
+        Ok(())
+    }
+}
+
+// Current settings, loaded once at startup
+static SETTINGS: Lazy<Mutex<Settings>> = Lazy::new(|| Mutex::new(Settings::default()));
+
+fn settings_path(app: &impl Host) -> Result<PathBuf, String> {
+    Ok(app.config_dir()?.join(SETTINGS_FILE))
+}
+
+/// Load settings from disk, falling back to defaults if the file is missing or invalid
+pub fn load(app: &impl Host) -> Result<(), String> {
+    let path = settings_path(app)?;
+
+    let settings = match fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str::<Settings>(&contents)
+            .map_err(|e| e.to_string())
+            .and_then(|settings| settings.validate().map(|_| settings))
+            .unwrap_or_else(|e| {
+                eprintln!("Invalid settings file {:?}, using defaults: {}", path, e);
+                Settings::default()
+            }),
+        Err(_) => Settings::default(),
+    };
+
+    *SETTINGS.lock().map_err(|e| format!("Failed to acquire lock: {}", e))? = settings;
+    Ok(())
+}
+
+/// Snapshot of the current settings
+pub fn get() -> Settings {
+    SETTINGS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+/// Replace the current settings and write them to disk
+pub fn save(app: &impl Host, settings: Settings) -> Result<(), String> {
+    settings.validate()?;
+
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write settings: {}", e))?;
+
+    *SETTINGS.lock().map_err(|e| format!("Failed to acquire lock: {}", e))? = settings;
+    Ok(())
+}
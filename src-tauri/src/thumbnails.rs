@@ -0,0 +1,229 @@
+// Thumbnail cache served to the webview through the `thumb://` URI scheme
+// The CSP only allows local images, so remote thumbnails are downloaded once
+// into the app cache dir and served from there
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use once_cell::sync::Lazy;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// URI scheme registered with the webview
+pub const SCHEME: &str = "thumb";
+
+/// Upper bound for the thumbnail cache, oldest files are evicted first
+const MAX_CACHE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Thumbnails larger than this are refused (YouTube's maxres images are ~200KB)
+const MAX_THUMBNAIL_BYTES: usize = 5 * 1024 * 1024;
+
+/// Video ids remembered at most in THUMBNAIL_SOURCES before it is cleared
+const MAX_SOURCES: usize = 10_000;
+
+// Remote thumbnail URL for each video id seen in fetched metadata
+static THUMBNAIL_SOURCES: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Only plain video ids are used as cache keys (no path separators or dots)
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Remember where the thumbnail for a video lives and return the local URL for it
+pub fn register(id: &str, remote_url: Option<&str>) -> Option<String> {
+    if !is_valid_id(id) {
+        return None;
+    }
+
+    if let Some(remote_url) = remote_url {
+        if remote_url.starts_with("https://") || remote_url.starts_with("http://") {
+            if let Ok(mut sources) = THUMBNAIL_SOURCES.lock() {
+                if sources.len() >= MAX_SOURCES && !sources.contains_key(id) {
+                    sources.clear();
+                }
+                sources.insert(id.to_string(), remote_url.to_string());
+            }
+        }
+    }
+
+    Some(protocol_url(id))
+}
+
+/// URL the webview uses to load a cached thumbnail
+/// Windows and Android serve custom schemes as http://<scheme>.localhost
+fn protocol_url(id: &str) -> String {
+    if cfg!(any(target_os = "windows", target_os = "android")) {
+        format!("http://{}.localhost/{}", SCHEME, id)
+    } else {
+        format!("{}://localhost/{}", SCHEME, id)
+    }
+}
+
+fn cache_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let dir = app.path().app_cache_dir()
+        .map_err(|e| format!("Failed to get cache dir: {}", e))?
+        .join("thumbnails");
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create thumbnail cache dir: {}", e))?;
+    Ok(dir)
+}
+
+/// Handle a `thumb://localhost/<video id>` request
+pub fn handle_request<R: Runtime>(app: &AppHandle<R>, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let id = request.uri().path().trim_start_matches('/').to_string();
+
+    if !is_valid_id(&id) {
+        return error_response(StatusCode::BAD_REQUEST, "Invalid thumbnail id");
+    }
+
+    match load_thumbnail(app, &id) {
+        Ok(bytes) => Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type(&bytes))
+            .header(header::CACHE_CONTROL, "max-age=86400")
+            .body(bytes)
+            .unwrap_or_else(|_| error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to build response")),
+        Err(e) => {
+            println!("thumbnails: Failed to load thumbnail for {}: {}", id, e);
+            error_response(StatusCode::NOT_FOUND, &e)
+        }
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(message.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+/// Serve from the cache, downloading the thumbnail on a miss
+fn load_thumbnail<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<Vec<u8>, String> {
+    let dir = cache_dir(app)?;
+    let path = dir.join(id);
+
+    if let Ok(bytes) = fs::read(&path) {
+        // Bump the modification time so eviction drops the least recently used files
+        if let Ok(file) = File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        return Ok(bytes);
+    }
+
+    // Only thumbnails named by fetched metadata are downloaded
+    let remote_url = THUMBNAIL_SOURCES.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?
+        .get(id)
+        .cloned()
+        .ok_or_else(|| "Unknown thumbnail".to_string())?;
+
+    let response = minreq::get(&remote_url)
+        .with_timeout(15)
+        .with_max_redirects(5)
+        .send()
+        .map_err(|e| format!("Failed to download thumbnail: {}", e))?;
+
+    if response.status_code < 200 || response.status_code >= 300 {
+        return Err(format!("Failed to download thumbnail: HTTP {}", response.status_code));
+    }
+
+    let bytes = response.into_bytes();
+    if bytes.is_empty() || bytes.len() > MAX_THUMBNAIL_BYTES {
+        return Err(format!("Refusing thumbnail of {} bytes", bytes.len()));
+    }
+
+    // Write to a temp file first so a concurrent request never reads a partial image
+    let temp_path = dir.join(format!("{}.tmp", id));
+    File::create(&temp_path)
+        .and_then(|mut file| file.write_all(&bytes))
+        .and_then(|_| fs::rename(&temp_path, &path))
+        .map_err(|e| format!("Failed to cache thumbnail: {}", e))?;
+
+    if let Err(e) = evict(&dir, MAX_CACHE_BYTES) {
+        println!("thumbnails: Eviction failed: {}", e);
+    }
+
+    Ok(bytes)
+}
+
+/// Remove the least recently used thumbnails until the cache fits within `max_bytes`
+fn evict(dir: &Path, max_bytes: u64) -> Result<(), String> {
+    let mut files: Vec<(PathBuf, u64, SystemTime)> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read cache dir: {}", e))?
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| {
+                (entry.path(), metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH))
+            })
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    if total <= max_bytes {
+        return Ok(());
+    }
+
+    files.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sniff the image type, YouTube serves both JPEG and WebP
+fn content_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"\x89PNG") {
+        "image/png"
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let dir = std::env::temp_dir().join(format!("verdl-thumbnails-test-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // 100 bytes each, "a" used longest ago
+        for (id, age) in [("a", 400), ("b", 300), ("c", 200), ("d", 100)] {
+            let path = dir.join(id);
+            fs::write(&path, [0u8; 100]).unwrap();
+            let modified = SystemTime::now() - Duration::from_secs(age);
+            File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        }
+
+        evict(&dir, 250).unwrap();
+        let mut left: Vec<String> = fs::read_dir(&dir).unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+
+        evict(&dir, 1_000).unwrap();
+        let unchanged = fs::read_dir(&dir).unwrap().count();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(left, ["c", "d"]);
+        assert_eq!(unchanged, 2);
+    }
+}
@@ -0,0 +1,259 @@
+// Safe updates of the managed yt-dlp
+// The current binary is kept in a small version history before updating, and the updated
+// one has to pass a smoke test (`--version` plus a simulated extraction) or the previous
+// binary is put back. Older versions in the history can be restored by hand
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use crate::errors::{self, ErrorCategory};
+use crate::host::Host;
+use crate::{local_ytdlp_command, ytdlp_binary_name};
+
+/// Previous versions live in <local data>/yt-dlp-versions/<version>/
+const VERSIONS_DIR: &str = "yt-dlp-versions";
+
+/// Versions kept at most, the oldest is dropped first
+const MAX_VERSIONS: usize = 5;
+
+/// When a version was saved (Unix seconds), next to its binary; file times don't say,
+/// copying keeps or resets them depending on the platform
+const SAVED_AT_FILE: &str = "saved-at";
+
+/// yt-dlp's own test video, extracted (not downloaded) by the smoke test
+const SMOKE_TEST_URL: &str = "https://www.youtube.com/watch?v=BaW_jenozKc";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedVersion {
+    pub version: String,
+    pub saved_at: u64, // Unix seconds
+    pub current: bool,
+}
+
+fn versions_dir(local_data_dir: &Path) -> PathBuf {
+    local_data_dir.join(VERSIONS_DIR)
+}
+
+/// Copy `from` over `to` without ever leaving a partial file at `to`
+fn replace_file(from: &Path, to: &Path) -> Result<(), String> {
+    let temp = to.with_extension(format!("restore-{}", uuid::Uuid::new_v4().simple()));
+    fs::copy(from, &temp)
+        .and_then(|_| fs::rename(&temp, to))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            format!("Failed to replace {}: {}", to.display(), e)
+        })
+}
+
+/// Keep a copy of the binary under its version
+fn save_version(ytdlp: &Path, version: &str, local_data_dir: &Path) -> Result<(), String> {
+    let dir = versions_dir(local_data_dir).join(version);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to back up yt-dlp: {}", e))?;
+    fs::copy(ytdlp, dir.join(ytdlp_binary_name()))
+        .map_err(|e| format!("Failed to back up yt-dlp: {}", e))?;
+    mark_saved(&dir);
+    prune_versions(local_data_dir);
+    Ok(())
+}
+
+/// Record now as the time a version was saved (or last used)
+fn mark_saved(dir: &Path) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    if let Err(e) = fs::write(dir.join(SAVED_AT_FILE), now.to_string()) {
+        eprintln!("update: Failed to record when {:?} was saved: {}", dir, e);
+    }
+}
+
+/// When the version in `dir` was saved, falling back to the binary's mtime for
+/// versions saved before the saved-at file existed
+fn saved_at(dir: &Path) -> u64 {
+    fs::read_to_string(dir.join(SAVED_AT_FILE)).ok()
+        .and_then(|saved_at| saved_at.trim().parse().ok())
+        .or_else(|| {
+            fs::metadata(dir.join(ytdlp_binary_name())).ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+        })
+        .unwrap_or(0)
+}
+
+fn prune_versions(local_data_dir: &Path) {
+    let mut saved = saved_versions(local_data_dir);
+    saved.sort_by_key(|(_, saved_at)| std::cmp::Reverse(*saved_at));
+    for (version, _) in saved.into_iter().skip(MAX_VERSIONS) {
+        let _ = fs::remove_dir_all(versions_dir(local_data_dir).join(version));
+    }
+}
+
+/// (version, saved at) of every saved binary
+fn saved_versions(local_data_dir: &Path) -> Vec<(String, u64)> {
+    let Ok(entries) = fs::read_dir(versions_dir(local_data_dir)) else {
+        return Vec::new();
+    };
+
+    entries.flatten()
+        .filter(|entry| entry.path().join(ytdlp_binary_name()).is_file())
+        .map(|entry| (entry.file_name().to_string_lossy().to_string(), saved_at(&entry.path())))
+        .collect()
+}
+
+/// Saved versions, newest first, marking the one that is installed
+pub fn list_versions(local_data_dir: &Path, installed: Option<&str>) -> Vec<SavedVersion> {
+    let mut versions: Vec<SavedVersion> = saved_versions(local_data_dir).into_iter()
+        .map(|(version, saved_at)| SavedVersion { current: Some(version.as_str()) == installed, version, saved_at })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.saved_at));
+    versions
+}
+
+/// Check that a binary runs and can still extract a video
+/// Failures that say nothing about the binary (no network, bot check) don't count
+fn smoke_test(app: &impl Host, ytdlp: &Path) -> Result<String, String> {
+    let version = crate::installed_ytdlp_version(ytdlp)
+        .ok_or_else(|| "yt-dlp --version failed".to_string())?;
+
+    // Whether yt-dlp takes --js-runtimes is remembered per path, which now holds another version
+    crate::js_runtime::reset();
+
+    // Same runtime, extractor arguments and request profile as real downloads
+    let output = crate::ytdlp_command(app, ytdlp, SMOKE_TEST_URL)
+        .args(["--simulate", "--no-playlist", "--no-warnings", SMOKE_TEST_URL])
+        .output()
+        .map_err(|e| format!("Failed to execute yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        match errors::classify(&stderr) {
+            ErrorCategory::Network | ErrorCategory::BotCheck | ErrorCategory::Unavailable => {
+                eprintln!("update: Smoke test extraction inconclusive: {}", stderr);
+            }
+            _ => return Err(format!("simulated extraction failed: {}", stderr)),
+        }
+    }
+
+    Ok(version)
+}
+
+/// Let yt-dlp update itself to `target` (see install::update_target), returning its output
+pub fn self_update(ytdlp: &Path, target: &str) -> Result<String, String> {
+    println!("update: Updating yt-dlp at {:?} to {}", ytdlp, target);
+    let output = local_ytdlp_command(ytdlp)
+        .args(["--update-to", target])
+        .output()
+        .map_err(|e| format!("Failed to run yt-dlp update: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!("Failed to update yt-dlp: {}", String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+/// Update the managed binary at `ytdlp` with `install`, restoring the previous binary
+/// if the new one fails the smoke test
+pub fn safe_update(
+    app: &impl Host,
+    ytdlp: &Path,
+    local_data_dir: &Path,
+    install: impl FnOnce() -> Result<String, String>,
+) -> Result<String, String> {
+    let previous = crate::installed_ytdlp_version(ytdlp)
+        .ok_or_else(|| "Failed to update yt-dlp: the installed yt-dlp doesn't run".to_string())?;
+    save_version(ytdlp, &previous, local_data_dir)?;
+    let backup = versions_dir(local_data_dir).join(&previous).join(ytdlp_binary_name());
+
+    let stdout = match install() {
+        Ok(stdout) => stdout,
+        Err(e) => {
+            // A failed self-update may have left anything behind
+            replace_file(&backup, ytdlp)?;
+            return Err(e);
+        }
+    };
+
+    match smoke_test(app, ytdlp) {
+        Ok(version) if version == previous => Ok(format!("yt-dlp {} is up to date.\n{}", version, stdout)),
+        Ok(version) => {
+            // Also keep the new version, so it can be restored after a rollback
+            let _ = save_version(ytdlp, &version, local_data_dir);
+            Ok(format!("yt-dlp updated from {} to {}.\n{}", previous, version, stdout))
+        }
+        Err(e) => {
+            eprintln!("update: New yt-dlp failed the smoke test, restoring {}: {}", previous, e);
+            replace_file(&backup, ytdlp)?;
+            Err(format!("Failed to update yt-dlp: the new version {}. Restored {}.", e, previous))
+        }
+    }
+}
+
+/// Put a saved version back in place of the managed binary
+pub fn rollback(ytdlp: &Path, version: &str, local_data_dir: &Path) -> Result<String, String> {
+    let saved = versions_dir(local_data_dir).join(version).join(ytdlp_binary_name());
+    if !crate::install::is_valid_version(version) || !saved.is_file() {
+        return Err(format!("Invalid yt-dlp version '{}': not in the version history", version));
+    }
+
+    let restored = crate::installed_ytdlp_version(&saved)
+        .ok_or_else(|| format!("Failed to restore yt-dlp {}: the saved copy doesn't run", version))?;
+
+    if let Some(current) = crate::installed_ytdlp_version(ytdlp) {
+        save_version(ytdlp, &current, local_data_dir)?;
+    }
+    replace_file(&saved, ytdlp)?;
+
+    // Restoring counts as using that version now, keep it from being pruned first
+    mark_saved(&versions_dir(local_data_dir).join(version));
+
+    println!("update: Restored yt-dlp {}", restored);
+    Ok(format!("Restored yt-dlp {}.", restored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("verdl-update-test-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn orders_versions_by_saved_at_file() {
+        let local_data_dir = temp_dir();
+        for (version, saved_at) in [("2025.01.01", "300"), ("2025.02.01", "100"), ("2025.03.01", "200")] {
+            let dir = versions_dir(&local_data_dir).join(version);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join(ytdlp_binary_name()), version).unwrap();
+            fs::write(dir.join(SAVED_AT_FILE), saved_at).unwrap();
+        }
+
+        let versions = list_versions(&local_data_dir, Some("2025.03.01"));
+        let _ = fs::remove_dir_all(&local_data_dir);
+
+        let order: Vec<(&str, u64, bool)> = versions.iter().map(|v| (v.version.as_str(), v.saved_at, v.current)).collect();
+        assert_eq!(order, [("2025.01.01", 300, false), ("2025.03.01", 200, true), ("2025.02.01", 100, false)]);
+    }
+
+    #[test]
+    fn saving_records_the_save_time() {
+        let local_data_dir = temp_dir();
+        let binary = local_data_dir.join(ytdlp_binary_name());
+        fs::write(&binary, "old").unwrap();
+        // An old mtime would sort this version last if it were used
+        let old = UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        fs::File::options().write(true).open(&binary).unwrap().set_modified(old).unwrap();
+
+        save_version(&binary, "2024.01.01", &local_data_dir).unwrap();
+        let saved = saved_versions(&local_data_dir);
+        let _ = fs::remove_dir_all(&local_data_dir);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0].1 + 60 >= now, "saved at {} should be close to {}", saved[0].1, now);
+    }
+}
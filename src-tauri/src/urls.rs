@@ -0,0 +1,501 @@
+// URL parsing and classification
+// Strict on purpose: only well-formed URLs on allowed hosts are accepted. YouTube URLs
+// must carry valid ids and yt-dlp is handed the reconstructed canonical URL rather than user input
+
+use serde::Serialize;
+
+/// What a YouTube URL points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlKind {
+    Video,
+    Short,
+    Live,
+    Playlist,
+    VideoInPlaylist,
+    Channel,
+    ChannelTab,
+    Search,
+    Other, // Any page on another allowed site, left to yt-dlp's extractors
+}
+
+impl UrlKind {
+    /// Whether metadata should be fetched as a list of entries rather than a single video
+    /// Other sites may be either, so they go through the playlist path which handles both
+    pub fn is_collection(self) -> bool {
+        matches!(self, UrlKind::Playlist | UrlKind::VideoInPlaylist | UrlKind::Channel | UrlKind::ChannelTab | UrlKind::Search | UrlKind::Other)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParsedUrl {
+    pub kind: UrlKind,
+    pub video_id: Option<String>,
+    pub playlist_id: Option<String>,
+    pub channel: Option<String>, // "@handle", "channel/UC...", "c/name" or "user/name"
+    pub tab: Option<String>,     // videos, shorts, streams, playlists, ...
+    pub query: Option<String>,   // Search terms (still URL-encoded)
+    pub canonical_url: String,
+}
+
+impl ParsedUrl {
+    /// Identity of what the URL points to, so different URLs for the same video or playlist compare equal
+    pub fn dedup_key(&self) -> String {
+        match (&self.video_id, &self.playlist_id) {
+            (_, Some(playlist_id)) => format!("playlist:{}", playlist_id),
+            (Some(video_id), None) => format!("video:{}", video_id),
+            _ => self.canonical_url.clone(),
+        }
+    }
+
+    /// URL to enumerate for metadata: a bare channel lists its tabs (Videos, Shorts, Live)
+    /// rather than videos, so its Videos tab is used instead
+    pub fn enumeration_url(&self) -> String {
+        match self.kind {
+            UrlKind::Channel => format!("{}/videos", self.canonical_url),
+            _ => self.canonical_url.clone(),
+        }
+    }
+}
+
+const MAX_URL_LENGTH: usize = 2048;
+
+/// Hosts handled by the YouTube-specific parser
+pub const YOUTUBE_DOMAINS: &[&str] = &["youtube.com", "youtu.be", "youtube-nocookie.com"];
+
+const CHANNEL_TABS: &[&str] = &[
+    "featured", "videos", "shorts", "streams", "live", "playlists", "podcasts", "releases", "community", "search",
+];
+
+/// Video ids are always 11 characters of [A-Za-z0-9_-]
+fn is_video_id(id: &str) -> bool {
+    id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Playlist ids vary in length by type (PL..., UU..., OLAK5uy_..., RD...)
+fn is_playlist_id(id: &str) -> bool {
+    (2..=64).contains(&id.len()) && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_handle(handle: &str) -> bool {
+    (3..=30).contains(&handle.len()) && handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+fn is_channel_name(name: &str) -> bool {
+    (1..=100).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+fn is_search_query(query: &str) -> bool {
+    !query.is_empty() && query.len() <= 500 && query.chars().all(|c| c.is_ascii_alphanumeric() || "%+-_.~".contains(c))
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&')
+        .find_map(|pair| pair.strip_prefix(name).and_then(|v| v.strip_prefix('=')))
+        .filter(|v| !v.is_empty())
+}
+
+/// Whether a string is a plain lowercase domain name, as used in the site allowlist
+pub fn is_valid_domain(domain: &str) -> bool {
+    let labels: Vec<&str> = domain.split('.').collect();
+    domain.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+fn host_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Whether a host belongs to YouTube (and gets the YouTube-specific parser)
+pub fn is_youtube_host(host: &str) -> bool {
+    YOUTUBE_DOMAINS.iter().any(|domain| host_matches(host, domain))
+}
+
+/// Lowercased host of an http(s) URL, rejecting userinfo and explicit ports
+pub fn host(url: &str) -> Option<String> {
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    if authority.is_empty() || authority.contains(['@', ':']) {
+        return None;
+    }
+    Some(authority.to_ascii_lowercase())
+}
+
+/// Parse a URL from any site in `allowed_sites` (domains, subdomains included)
+/// YouTube URLs get full classification, other sites only a strict syntax check
+pub fn parse_allowed(url: &str, allowed_sites: &[String]) -> Option<ParsedUrl> {
+    let host = host(url)?;
+    if !allowed_sites.iter().any(|site| host_matches(&host, site)) {
+        return None;
+    }
+
+    if is_youtube_host(&host) {
+        return parse(url);
+    }
+
+    // Arguments reach yt-dlp without a shell, but keep to plain URL characters anyway
+    let is_safe = url.len() <= MAX_URL_LENGTH
+        && is_valid_domain(&host)
+        && url.chars().all(|c| c.is_ascii_alphanumeric() || "-._~:/?#[]@!&+,=%".contains(c));
+
+    is_safe.then(|| ParsedUrl {
+        kind: UrlKind::Other,
+        video_id: None,
+        playlist_id: None,
+        channel: None,
+        tab: None,
+        query: None,
+        canonical_url: url.to_string(),
+    })
+}
+
+/// Parse and classify a YouTube URL, returning None for anything that isn't recognized
+pub fn parse(url: &str) -> Option<ParsedUrl> {
+    if url.len() > MAX_URL_LENGTH || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return None;
+    }
+
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (host_and_path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = host_and_path.split_once('/').unwrap_or((host_and_path, ""));
+    let host = host.to_ascii_lowercase();
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    let list = query_param(query, "list").filter(|id| is_playlist_id(id));
+
+    match host.as_str() {
+        "youtu.be" | "www.youtu.be" => {
+            let id = *segments.first()?;
+            (segments.len() == 1 && is_video_id(id)).then(|| video(id, list))
+        }
+        "youtube.com" | "www.youtube.com" | "m.youtube.com" | "music.youtube.com"
+        | "youtube-nocookie.com" | "www.youtube-nocookie.com" => parse_youtube_path(&segments, query, list),
+        _ => None,
+    }
+}
+
+fn parse_youtube_path(segments: &[&str], query: &str, list: Option<&str>) -> Option<ParsedUrl> {
+    match segments {
+        ["watch"] => {
+            let id = query_param(query, "v")?;
+            is_video_id(id).then(|| video(id, list))
+        }
+        ["playlist"] => list.map(playlist),
+        ["embed", "videoseries"] => list.map(playlist),
+        ["shorts", id] if is_video_id(id) => Some(ParsedUrl {
+            kind: UrlKind::Short,
+            video_id: Some(id.to_string()),
+            playlist_id: None,
+            channel: None,
+            tab: None,
+            query: None,
+            canonical_url: format!("https://www.youtube.com/shorts/{}", id),
+        }),
+        ["live", id] if is_video_id(id) => Some(ParsedUrl {
+            kind: UrlKind::Live,
+            video_id: Some(id.to_string()),
+            playlist_id: None,
+            channel: None,
+            tab: None,
+            query: None,
+            canonical_url: format!("https://www.youtube.com/watch?v={}", id),
+        }),
+        ["embed" | "v" | "e", id] if is_video_id(id) => Some(video(id, list)),
+        ["results"] => {
+            let terms = query_param(query, "search_query").or_else(|| query_param(query, "q"))?;
+            is_search_query(terms).then(|| ParsedUrl {
+                kind: UrlKind::Search,
+                video_id: None,
+                playlist_id: None,
+                channel: None,
+                tab: None,
+                query: Some(terms.to_string()),
+                canonical_url: format!("https://www.youtube.com/results?search_query={}", terms),
+            })
+        }
+        [handle, tab @ ..] if handle.starts_with('@') && is_handle(&handle[1..]) => channel(handle.to_string(), tab),
+        ["channel", id, tab @ ..] if id.starts_with("UC") && id.len() == 24 && is_playlist_id(id) => {
+            channel(format!("channel/{}", id), tab)
+        }
+        [kind @ ("c" | "user"), name, tab @ ..] if is_channel_name(name) => channel(format!("{}/{}", kind, name), tab),
+        _ => None,
+    }
+}
+
+fn video(id: &str, list: Option<&str>) -> ParsedUrl {
+    match list {
+        Some(list) => ParsedUrl {
+            kind: UrlKind::VideoInPlaylist,
+            video_id: Some(id.to_string()),
+            playlist_id: Some(list.to_string()),
+            channel: None,
+            tab: None,
+            query: None,
+            canonical_url: format!("https://www.youtube.com/watch?v={}&list={}", id, list),
+        },
+        None => ParsedUrl {
+            kind: UrlKind::Video,
+            video_id: Some(id.to_string()),
+            playlist_id: None,
+            channel: None,
+            tab: None,
+            query: None,
+            canonical_url: format!("https://www.youtube.com/watch?v={}", id),
+        },
+    }
+}
+
+fn playlist(list: &str) -> ParsedUrl {
+    ParsedUrl {
+        kind: UrlKind::Playlist,
+        video_id: None,
+        playlist_id: Some(list.to_string()),
+        channel: None,
+        tab: None,
+        query: None,
+        canonical_url: format!("https://www.youtube.com/playlist?list={}", list),
+    }
+}
+
+fn channel(channel: String, tab: &[&str]) -> Option<ParsedUrl> {
+    let tab = match tab {
+        [] => None,
+        [tab] if CHANNEL_TABS.contains(tab) => Some(tab.to_string()),
+        _ => return None,
+    };
+
+    let canonical_url = match &tab {
+        Some(tab) => format!("https://www.youtube.com/{}/{}", channel, tab),
+        None => format!("https://www.youtube.com/{}", channel),
+    };
+
+    Some(ParsedUrl {
+        kind: if tab.is_some() { UrlKind::ChannelTab } else { UrlKind::Channel },
+        video_id: None,
+        playlist_id: None,
+        channel: Some(channel),
+        tab,
+        query: None,
+        canonical_url,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (url, kind, video id, playlist id, canonical url)
+    type Case = (&'static str, UrlKind, Option<&'static str>, Option<&'static str>, &'static str);
+
+    const ID: &str = "dQw4w9WgXcQ";
+    const LIST: &str = "PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf";
+
+    #[test]
+    fn classifies_urls() {
+        let cases: &[Case] = &[
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("http://youtube.com/watch?v=dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42s", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ#comments", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://m.youtube.com/watch?v=dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://music.youtube.com/watch?v=dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://WWW.YouTube.com/watch?v=dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://youtu.be/dQw4w9WgXcQ?si=abc123&t=10", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/embed/dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?rel=0", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/v/dQw4w9WgXcQ", UrlKind::Video, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/shorts/dQw4w9WgXcQ", UrlKind::Short, Some(ID), None,
+                "https://www.youtube.com/shorts/dQw4w9WgXcQ"),
+            ("https://youtube.com/shorts/dQw4w9WgXcQ?feature=share", UrlKind::Short, Some(ID), None,
+                "https://www.youtube.com/shorts/dQw4w9WgXcQ"),
+            ("https://www.youtube.com/live/dQw4w9WgXcQ", UrlKind::Live, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/live/dQw4w9WgXcQ?si=xyz", UrlKind::Live, Some(ID), None,
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ"),
+            ("https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf", UrlKind::Playlist, None, Some(LIST),
+                "https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"),
+            ("https://music.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf", UrlKind::Playlist, None, Some(LIST),
+                "https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"),
+            ("https://www.youtube.com/embed/videoseries?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf", UrlKind::Playlist, None, Some(LIST),
+                "https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf&index=3", UrlKind::VideoInPlaylist, Some(ID), Some(LIST),
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"),
+            ("https://youtu.be/dQw4w9WgXcQ?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf", UrlKind::VideoInPlaylist, Some(ID), Some(LIST),
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"),
+            ("https://www.youtube.com/@LinusTechTips", UrlKind::Channel, None, None,
+                "https://www.youtube.com/@LinusTechTips"),
+            ("https://m.youtube.com/@some.handle_1", UrlKind::Channel, None, None,
+                "https://www.youtube.com/@some.handle_1"),
+            ("https://www.youtube.com/channel/UCXuqSBlHAE6Xw-yeJA0Tunw", UrlKind::Channel, None, None,
+                "https://www.youtube.com/channel/UCXuqSBlHAE6Xw-yeJA0Tunw"),
+            ("https://www.youtube.com/c/LinusTechTips", UrlKind::Channel, None, None,
+                "https://www.youtube.com/c/LinusTechTips"),
+            ("https://www.youtube.com/user/LinusTechTips/", UrlKind::Channel, None, None,
+                "https://www.youtube.com/user/LinusTechTips"),
+            ("https://www.youtube.com/@LinusTechTips/videos", UrlKind::ChannelTab, None, None,
+                "https://www.youtube.com/@LinusTechTips/videos"),
+            ("https://www.youtube.com/@LinusTechTips/streams", UrlKind::ChannelTab, None, None,
+                "https://www.youtube.com/@LinusTechTips/streams"),
+            ("https://www.youtube.com/channel/UCXuqSBlHAE6Xw-yeJA0Tunw/shorts", UrlKind::ChannelTab, None, None,
+                "https://www.youtube.com/channel/UCXuqSBlHAE6Xw-yeJA0Tunw/shorts"),
+            ("https://www.youtube.com/results?search_query=rust+tauri", UrlKind::Search, None, None,
+                "https://www.youtube.com/results?search_query=rust+tauri"),
+            ("https://www.youtube.com/results?search_query=caf%C3%A9&sp=EgIQAQ%253D%253D", UrlKind::Search, None, None,
+                "https://www.youtube.com/results?search_query=caf%C3%A9"),
+        ];
+
+        for (url, kind, video_id, playlist_id, canonical) in cases {
+            let parsed = parse(url).unwrap_or_else(|| panic!("expected {} to parse", url));
+            assert_eq!(parsed.kind, *kind, "kind for {}", url);
+            assert_eq!(parsed.video_id.as_deref(), *video_id, "video id for {}", url);
+            assert_eq!(parsed.playlist_id.as_deref(), *playlist_id, "playlist id for {}", url);
+            assert_eq!(parsed.canonical_url, *canonical, "canonical url for {}", url);
+        }
+    }
+
+    #[test]
+    fn extracts_channel_and_tab() {
+        let parsed = parse("https://www.youtube.com/@LinusTechTips/playlists").unwrap();
+        assert_eq!(parsed.channel.as_deref(), Some("@LinusTechTips"));
+        assert_eq!(parsed.tab.as_deref(), Some("playlists"));
+
+        let parsed = parse("https://www.youtube.com/results?search_query=lofi+beats").unwrap();
+        assert_eq!(parsed.query.as_deref(), Some("lofi+beats"));
+    }
+
+    #[test]
+    fn enumerates_channel_videos_tab() {
+        let cases = [
+            ("https://www.youtube.com/@LinusTechTips", "https://www.youtube.com/@LinusTechTips/videos"),
+            ("https://www.youtube.com/user/LinusTechTips/", "https://www.youtube.com/user/LinusTechTips/videos"),
+            ("https://www.youtube.com/@LinusTechTips/streams", "https://www.youtube.com/@LinusTechTips/streams"),
+            ("https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf",
+                "https://www.youtube.com/playlist?list=PLrAXtmErZgOeiKm4sgNOknGvNjby9efdf"),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(parse(url).unwrap().enumeration_url(), expected, "enumeration url for {}", url);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_urls() {
+        let cases = [
+            "",
+            "dQw4w9WgXcQ",
+            "www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "ftp://www.youtube.com/watch?v=dQw4w9WgXcQ",
+            "javascript:alert(1)",
+            "https://www.youtube.com/",
+            "https://www.youtube.com/watch",
+            "https://www.youtube.com/watch?v=",
+            "https://www.youtube.com/watch?v=short",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQX",
+            "https://www.youtube.com/watch?v=dQw4w9WgX;Q",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ --exec rm",
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ\n--exec",
+            "https://youtu.be/",
+            "https://youtu.be/dQw4w9WgXcQ/extra",
+            "https://www.youtube.com/shorts/",
+            "https://www.youtube.com/shorts/not-an-id",
+            "https://www.youtube.com/live/",
+            "https://www.youtube.com/playlist",
+            "https://www.youtube.com/playlist?list=",
+            "https://www.youtube.com/playlist?list=PL$bad",
+            "https://www.youtube.com/@",
+            "https://www.youtube.com/@ab",
+            "https://www.youtube.com/@handle/unknowntab",
+            "https://www.youtube.com/@handle/videos/extra",
+            "https://www.youtube.com/channel/notachannel",
+            "https://www.youtube.com/results",
+            "https://www.youtube.com/results?search_query=a;b",
+            "https://www.youtube.com.evil.com/watch?v=dQw4w9WgXcQ",
+            "https://evilyoutube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtube.com@evil.com/watch?v=dQw4w9WgXcQ",
+            "https://vimeo.com/123456",
+        ];
+
+        for url in cases {
+            assert_eq!(parse(url), None, "expected {:?} to be rejected", url);
+        }
+    }
+
+    #[test]
+    fn allowlist() {
+        let sites: Vec<String> = ["youtube.com", "youtu.be", "vimeo.com", "twitch.tv"]
+            .iter().map(|s| s.to_string()).collect();
+
+        let parsed = parse_allowed("https://youtu.be/dQw4w9WgXcQ", &sites).unwrap();
+        assert_eq!(parsed.kind, UrlKind::Video);
+
+        let parsed = parse_allowed("https://vimeo.com/76979871", &sites).unwrap();
+        assert_eq!(parsed.kind, UrlKind::Other);
+        assert_eq!(parsed.canonical_url, "https://vimeo.com/76979871");
+
+        assert!(parse_allowed("https://player.vimeo.com/video/76979871", &sites).is_some());
+        assert!(parse_allowed("https://www.twitch.tv/videos/1234567890", &sites).is_some());
+
+        // YouTube URLs still need valid ids, even when allowed
+        assert!(parse_allowed("https://www.youtube.com/watch?v=bad", &sites).is_none());
+        // Not in the allowlist
+        assert!(parse_allowed("https://soundcloud.com/artist/track", &sites).is_none());
+        assert!(parse_allowed("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ", &sites).is_none());
+        // Lookalike hosts, userinfo and ports
+        assert!(parse_allowed("https://notvimeo.com/76979871", &sites).is_none());
+        assert!(parse_allowed("https://vimeo.com.evil.com/76979871", &sites).is_none());
+        assert!(parse_allowed("https://vimeo.com@evil.com/76979871", &sites).is_none());
+        assert!(parse_allowed("https://vimeo.com:8080/76979871", &sites).is_none());
+        // Characters outside plain URLs
+        assert!(parse_allowed("https://vimeo.com/76979871 --exec id", &sites).is_none());
+        assert!(parse_allowed("https://vimeo.com/$(id)", &sites).is_none());
+        assert!(parse_allowed("https://vimeo.com/a;b", &sites).is_none());
+        assert!(parse_allowed("--exec https://vimeo.com/1", &sites).is_none());
+        assert!(parse_allowed("file:///etc/passwd", &sites).is_none());
+    }
+
+    #[test]
+    fn domains() {
+        assert!(is_valid_domain("vimeo.com"));
+        assert!(is_valid_domain("clips.twitch.tv"));
+        assert!(!is_valid_domain("localhost"));
+        assert!(!is_valid_domain("Vimeo.com"));
+        assert!(!is_valid_domain("vimeo..com"));
+        assert!(!is_valid_domain("-vimeo.com"));
+        assert!(!is_valid_domain("vimeo.com/"));
+        assert!(!is_valid_domain("*.vimeo.com"));
+    }
+
+    #[test]
+    fn collection_kinds() {
+        assert!(!UrlKind::Video.is_collection());
+        assert!(!UrlKind::Short.is_collection());
+        assert!(!UrlKind::Live.is_collection());
+        assert!(UrlKind::Playlist.is_collection());
+        assert!(UrlKind::VideoInPlaylist.is_collection());
+        assert!(UrlKind::Channel.is_collection());
+        assert!(UrlKind::ChannelTab.is_collection());
+        assert!(UrlKind::Search.is_collection());
+        assert!(UrlKind::Other.is_collection());
+    }
+}
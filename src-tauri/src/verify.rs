@@ -0,0 +1,171 @@
+// Verification of downloaded yt-dlp binaries
+// Every yt-dlp release publishes SHA2-256SUMS (and a GPG signature of it, SHA2-256SUMS.sig)
+// next to the binaries; nothing is installed unless its checksum is listed there.
+// Mirrors have to carry these files as well
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use crate::{install, settings};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+const SUMS_FILE: &str = "SHA2-256SUMS";
+const SIGNATURE_FILE: &str = "SHA2-256SUMS.sig";
+
+/// Checksum listed for `asset` in a `sha256sum`-style file ("<hex>  <name>" or "<hex> *<name>")
+fn expected_checksum(sums: &str, asset: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        let name = name.trim_start().trim_start_matches('*');
+        (name == asset && hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| hash.to_lowercase())
+    })
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    // A mirror on disk has its checksum files next to the binaries too
+    if let Some(path) = install::local_path(url) {
+        return fs::read(&path)
+            .map_err(|e| format!("Failed to verify yt-dlp download: couldn't read {}: {}", path.display(), e));
+    }
+
+    let response = minreq::get(url)
+        .with_timeout(30)
+        .send()
+        .map_err(|e| format!("Failed to verify yt-dlp download: couldn't fetch {}: {}", url, e))?;
+
+    if response.status_code < 200 || response.status_code >= 300 {
+        return Err(format!("Failed to verify yt-dlp download: couldn't fetch {}: HTTP {}", url, response.status_code));
+    }
+    Ok(response.into_bytes())
+}
+
+/// Check the SHA-256 of the file downloaded from `download_url` against the release's
+/// SHA2-256SUMS, and the signature of that file when a signing key is configured
+pub fn verify_checksum(download_url: &str, actual: &str) -> Result<(), String> {
+    let (release_url, asset) = download_url.rsplit_once('/')
+        .ok_or_else(|| format!("Failed to verify yt-dlp download: unexpected URL {}", download_url))?;
+
+    let sums = fetch(&format!("{}/{}", release_url, SUMS_FILE))?;
+
+    if let Some(key) = settings::get().ytdlp_signing_key.filter(|key| !key.is_empty()) {
+        let signature = fetch(&format!("{}/{}", release_url, SIGNATURE_FILE))?;
+        verify_signature(&sums, &signature, Path::new(&key))?;
+        println!("verify: {} signature is valid", SUMS_FILE);
+    }
+
+    let sums = String::from_utf8_lossy(&sums);
+    let expected = expected_checksum(&sums, asset)
+        .ok_or_else(|| format!("Failed to verify yt-dlp download: {} has no entry for {}", SUMS_FILE, asset))?;
+
+    if actual != expected {
+        return Err(format!(
+            "Failed to verify yt-dlp download: checksum mismatch for {} (expected {}, got {}). The file was not installed.",
+            asset, expected, actual
+        ));
+    }
+
+    println!("verify: {} matches SHA-256 {}", asset, actual);
+    Ok(())
+}
+
+/// Verify the detached GPG signature of the checksum file against the pinned public key
+/// Uses a throwaway keyring so the user's own keys and trust settings play no part
+fn verify_signature(sums: &[u8], signature: &[u8], key: &Path) -> Result<(), String> {
+    let home = std::env::temp_dir().join(format!("verdl-gpg-{}", uuid::Uuid::new_v4()));
+    let result = (|| {
+        fs::create_dir_all(&home)
+            .map_err(|e| format!("Failed to verify yt-dlp download: {}", e))?;
+
+        // gpg refuses to use a home directory others can read
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&home, fs::Permissions::from_mode(0o700));
+        }
+
+        let sums_path = home.join(SUMS_FILE);
+        let signature_path = home.join(SIGNATURE_FILE);
+        fs::write(&sums_path, sums)
+            .and_then(|_| fs::write(&signature_path, signature))
+            .map_err(|e| format!("Failed to verify yt-dlp download: {}", e))?;
+
+        gpg(&home, &["--import".as_ref(), key.as_os_str()])?;
+        let status = gpg(&home, &[
+            "--status-fd".as_ref(), "1".as_ref(),
+            "--verify".as_ref(), signature_path.as_os_str(), sums_path.as_os_str(),
+        ])?;
+
+        // VALIDSIG is only printed for a good signature from a key in the keyring
+        if status.lines().any(|line| line.starts_with("[GNUPG:] VALIDSIG ")) {
+            Ok(())
+        } else {
+            Err(format!("Failed to verify yt-dlp download: {} is not signed by the configured key", SUMS_FILE))
+        }
+    })();
+
+    let _ = fs::remove_dir_all(&home);
+    result
+}
+
+/// Run gpg with its own home directory, returning stdout
+fn gpg(home: &Path, args: &[&std::ffi::OsStr]) -> Result<String, String> {
+    let mut cmd = Command::new("gpg");
+
+    // Windows: prevent console window
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(crate::CREATE_NO_WINDOW);
+
+    let output = cmd
+        .arg("--batch")
+        .arg("--homedir").arg(home)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to verify yt-dlp download: couldn't run gpg: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to verify yt-dlp download: gpg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn reads_text_and_binary_entries() {
+        let sums = format!("{}  yt-dlp\n{} *yt-dlp.exe\n", HASH, HASH.replace('0', "f"));
+        assert_eq!(expected_checksum(&sums, "yt-dlp"), Some(HASH.to_string()));
+        assert_eq!(expected_checksum(&sums, "yt-dlp.exe"), Some(HASH.replace('0', "f")));
+    }
+
+    #[test]
+    fn lowercases_uppercase_hashes() {
+        let sums = format!("{}  yt-dlp_macos\n", HASH.to_uppercase());
+        assert_eq!(expected_checksum(&sums, "yt-dlp_macos"), Some(HASH.to_string()));
+    }
+
+    #[test]
+    fn matches_the_whole_name() {
+        let sums = format!("{}  yt-dlp_linux\n{}  yt-dlp_linux.zip\n", HASH, HASH.replace('0', "f"));
+        assert_eq!(expected_checksum(&sums, "yt-dlp_linux.zip"), Some(HASH.replace('0', "f")));
+        assert_eq!(expected_checksum(&sums, "yt-dlp"), None);
+    }
+
+    #[test]
+    fn rejects_missing_and_malformed_entries() {
+        assert_eq!(expected_checksum("", "yt-dlp"), None);
+        assert_eq!(expected_checksum(&format!("{}  yt-dlp.exe", HASH), "yt-dlp"), None);
+        assert_eq!(expected_checksum("abc123  yt-dlp", "yt-dlp"), None);
+        assert_eq!(expected_checksum(&format!("{}  yt-dlp", HASH.replace('0', "g")), "yt-dlp"), None);
+        assert_eq!(expected_checksum(HASH, "yt-dlp"), None);
+    }
+}
@@ -23,6 +23,7 @@ const state = {
   concurrentDownloads: 1,
   activeDownloads: new Map(),
   downloadCounter: 0,
+  metadataRequestId: null, // Playlist enumeration in progress (for metadata-entry events)
   selectedVideos: new Set(), // Track which videos are selected for download
   cancelledDownloads: new Set(), // Track downloads that were cancelled by user
 };
@@ -92,7 +93,7 @@ function setupEventListeners() {
   // URL input
   elements.urlInput.addEventListener('input', handleUrlInput);
   elements.urlInput.addEventListener('keypress', (e) => {
-    if (e.key === 'Enter' && !elements.fetchBtn.disabled) {
+    if (e.key === 'Enter' && !elements.fetchBtn.disabled && !state.metadataRequestId) {
       handleFetchMetadata();
     }
   });
@@ -127,7 +128,7 @@ function setupEventListeners() {
 
 function setupTauriEventListeners() {
   listen('download-progress', (event) => {
-    const { id, progress, status, title, downloadType, converting } = event.payload;
+    const { id, progress, status, title, downloadType, converting, elapsed, recordedBytes } = event.payload;
 
     // If download was cancelled, remove it completely from UI
     if (status === 'cancelled') {
@@ -136,14 +137,115 @@ function setupTauriEventListeners() {
     }
 
     updateDownloadProgress(id, progress, status, title, downloadType, converting);
+
+    // Live recordings report elapsed time and recorded bytes instead of a percentage
+    const download = state.activeDownloads.get(id);
+    if (download && elapsed !== undefined) {
+      download.elapsed = elapsed;
+      download.recordedBytes = recordedBytes || 0;
+      updateDownloadsList();
+    }
+  });
+
+  // Large playlists are enumerated incrementally
+  listen('metadata-entry', (event) => {
+    const { requestId, count, total } = event.payload;
+    if (requestId !== state.metadataRequestId) {
+      return;
+    }
+
+    elements.inputStatus.textContent = total
+      ? `Retrieving metadata... ${count} of ${total} videos`
+      : `Retrieving metadata... ${count} videos`;
+  });
+
+  // yt-dlp is downloaded on first start (and when it goes missing)
+  listen('yt-dlp-install-progress', (event) => {
+    const { status, progress, attempt, maxAttempts } = event.payload;
+
+    if (status === 'downloading') {
+      elements.statusText.textContent = progress != null
+        ? `Installing ${Math.floor(progress)}%`
+        : 'Installing...';
+    } else if (status === 'verifying') {
+      elements.statusText.textContent = 'Verifying...';
+    } else if (status === 'retrying') {
+      elements.statusText.textContent = `Retrying (${attempt}/${maxAttempts})...`;
+    } else if (status === 'failed') {
+      elements.statusText.textContent = 'Install failed';
+      showToast('Failed to install yt-dlp', 'error');
+    }
+  });
+
+  // Deno is downloaded in the background when allowed and no JavaScript runtime was found
+  listen('js-runtime-install-progress', (event) => {
+    const { status, progress } = event.payload;
+
+    if (status === 'downloading') {
+      elements.statusText.textContent = progress != null
+        ? `Installing Deno ${Math.floor(progress)}%`
+        : 'Installing Deno...';
+    } else if (status === 'verifying') {
+      elements.statusText.textContent = 'Verifying Deno...';
+    } else if (status === 'installed') {
+      checkYtdlpStatus();
+    } else if (status === 'failed') {
+      showToast('Failed to install Deno, YouTube downloads may fail', 'error');
+      checkYtdlpStatus();
+    }
+  });
+
+  // Supported URLs copied while the clipboard watcher is enabled
+  listen('clipboard-url', (event) => {
+    const { url, queued } = event.payload;
+
+    if (queued) {
+      showToast(`Queued copied link: ${url}`, 'info');
+      return;
+    }
+
+    elements.urlInput.value = url;
+    elements.urlInput.dispatchEvent(new Event('input'));
+    showToast('Copied link ready to fetch', 'info');
+  });
+
+  // verdl:// links opened from other apps
+  listen('deep-link-download', (event) => {
+    showToast(`Queued link: ${event.payload.url}`, 'info');
+  });
+
+  listen('deep-link-error', (event) => {
+    showToast(`Ignored link: ${event.payload.error}`, 'error');
+  });
+
+  // yt-dlp is updated automatically when a download fails because it is out of date,
+  // and by the scheduled update check
+  listen('ytdlp-auto-update', (event) => {
+    const { status, message } = event.payload;
+
+    if (status === 'updated') {
+      showToast(message, 'success');
+      checkYtdlpStatus();
+    } else if (status === 'failed') {
+      showToast(`Automatic yt-dlp update failed: ${message}`, 'error');
+    } else {
+      showToast(message, 'info');
+    }
+  });
+
+  // Found by the scheduled update check; installed right away if configured to
+  listen('ytdlp-update-available', (event) => {
+    const { latest, pinned, autoInstall } = event.payload;
+    if (!autoInstall) {
+      showToast(`yt-dlp ${pinned || latest} is available. Click Update to install it.`, 'info');
+    }
   });
 
   listen('download-error', (event) => {
-    const { url, error } = event.payload;
-    const errorMsg = error.toLowerCase();
+    const { url, error, category } = event.payload;
 
     // Detect bot detection
-    if (errorMsg.includes('bot') || errorMsg.includes('sign in to confirm')) {
+    if (category === 'bot-check') {
       showToast('YouTube bot detected. Try updating yt-dlp.', 'warning');
     } else {
       showToast(`Download failed: ${error}`, 'error');
@@ -158,13 +260,23 @@ function setupTauriEventListeners() {
 async function checkYtdlpStatus() {
   try {
     console.log('Checking yt-dlp status...');
-    const installed = await invoke('check_ytdlp_installed');
-    console.log('yt-dlp installed result:', installed);
-    state.ytdlpInstalled = installed;
+    const status = await invoke('check_ytdlp_installed');
+    console.log('yt-dlp status:', status);
+    state.ytdlpInstalled = status.installed;
 
-    if (installed) {
+    if (status.installed) {
       elements.statusDot.classList.add('active');
       elements.statusText.textContent = 'Ready';
+      elements.ytdlpStatus.title = `yt-dlp ${status.version} (${status.source}): ${status.path}`;
+
+      // YouTube challenges need a JavaScript runtime with current yt-dlp versions
+      const runtime = status.runtime;
+      if (runtime && runtime.name) {
+        elements.ytdlpStatus.title += `\nJavaScript runtime: ${runtime.name} (${runtime.path})`;
+      } else if (runtime && runtime.supported) {
+        elements.ytdlpStatus.title += '\nNo JavaScript runtime found';
+        showToast('No JavaScript runtime (Deno, Node or Bun) found. YouTube downloads may fail.', 'warning');
+      }
     } else {
       elements.statusDot.classList.remove('active');
       elements.statusText.textContent = 'Not Found';
@@ -224,18 +336,26 @@ async function handleUpdateYtdlp() {
 // URL INPUT HANDLING
 // ═════════════════════════════════════════════════════════════════
 
-function handleUrlInput(e) {
+async function handleUrlInput(e) {
   const url = e.target.value.trim();
   state.currentUrl = url;
 
-  const isValidUrl = url && (
-    url.toLowerCase().includes('youtube.com') ||
-    url.toLowerCase().includes('youtu.be')
-  );
+  // The fetch button is the cancel button while a fetch is running
+  if (state.metadataRequestId) {
+    return;
+  }
 
-  elements.fetchBtn.disabled = !isValidUrl;
+  elements.fetchBtn.disabled = true;
   elements.inputStatus.textContent = '';
   elements.inputStatus.className = 'input-status';
+
+  // The backend checks the URL against the configured site allowlist
+  const isValidUrl = url ? await invoke('validate_url', { url }).catch(() => false) : false;
+
+  // Ignore results for input that has changed in the meantime
+  if (state.currentUrl === url) {
+    elements.fetchBtn.disabled = !isValidUrl;
+  }
 }
 
 // ═════════════════════════════════════════════════════════════════
@@ -243,6 +363,14 @@ function handleUrlInput(e) {
 // ═════════════════════════════════════════════════════════════════
 
 async function handleFetchMetadata() {
+  // A second click cancels the enumeration in progress
+  if (state.metadataRequestId) {
+    await invoke('cancel_metadata_fetch', { requestId: state.metadataRequestId }).catch((error) => {
+      console.error('Error cancelling metadata fetch:', error);
+    });
+    return;
+  }
+
   if (!state.ytdlpInstalled) {
     showToast('yt-dlp is not installed', 'error');
     return;
@@ -254,21 +382,27 @@ async function handleFetchMetadata() {
     return;
   }
 
-  elements.fetchBtn.disabled = true;
+  state.metadataRequestId = crypto.randomUUID();
   elements.fetchBtn.innerHTML = `
     <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
-      <path d="M12 2v4M12 18v4M4.93 4.93l2.83 2.83M16.24 16.24l2.83 2.83M2 12h4M18 12h4M4.93 19.07l2.83-2.83M16.24 7.76l2.83-2.83"/>
+      <line x1="18" y1="6" x2="6" y2="18"/>
+      <line x1="6" y1="6" x2="18" y2="18"/>
     </svg>
-    Fetching...
+    Cancel
   `;
   elements.inputStatus.textContent = 'Retrieving metadata...';
 
   try {
-    const metadata = await invoke('fetch_metadata', { url });
+    const metadata = await invoke('fetch_metadata', { url, requestId: state.metadataRequestId });
     state.currentMetadata = metadata;
     displayMetadata(metadata);
     showToast(`Found ${metadata.video_count} video(s)`, 'success');
   } catch (error) {
+    if (String(error) === 'Metadata fetch cancelled') {
+      elements.inputStatus.textContent = 'Cancelled';
+      return;
+    }
+
     console.error('Error fetching metadata:', error);
     elements.inputStatus.textContent = String(error);
     elements.inputStatus.classList.add('error');
@@ -281,6 +415,7 @@ async function handleFetchMetadata() {
       showToast(`Failed to fetch metadata: ${error}`, 'error');
     }
   } finally {
+    state.metadataRequestId = null;
     elements.fetchBtn.disabled = false;
     elements.fetchBtn.innerHTML = `
       <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
@@ -363,6 +498,17 @@ function createVideoItem(video, index) {
   indexSpan.textContent = index + 1;
   div.appendChild(indexSpan);
 
+  // Add thumbnail (served from the backend's local cache)
+  if (video.local_thumbnail) {
+    const thumb = document.createElement('img');
+    thumb.className = 'video-item-thumb';
+    thumb.src = video.local_thumbnail;
+    thumb.alt = '';
+    thumb.loading = 'lazy';
+    thumb.addEventListener('error', () => thumb.remove());
+    div.appendChild(thumb);
+  }
+
   // Add title
   const titleSpan = document.createElement('span');
   titleSpan.className = 'video-item-title';
@@ -636,7 +782,7 @@ function updateDownloadProgress(id, progress, status, title, downloadType, conve
     });
   } else {
     const download = state.activeDownloads.get(id);
-    download.progress = progress;
+    if (progress !== undefined) download.progress = progress;
     download.status = status;
     if (title) download.title = title;
     if (converting !== undefined) download.converting = converting;
@@ -658,7 +804,8 @@ function updateDownloadsList() {
     d.status === 'downloading' ||
     d.status === 'starting' ||
     d.status === 'converting' ||
-    d.status === 'download_complete'
+    d.status === 'download_complete' ||
+    isLiveStatus(d.status)
   );
 
   // Update counter to show only active downloads and conversions
@@ -744,7 +891,8 @@ function createDownloadItem(download) {
   const isDownloading = statusClass === 'downloading';
   const isConverting = statusClass === 'converting';
   const isDownloadComplete = statusClass === 'download_complete';
-  const isCancellable = isDownloading || isConverting || isDownloadComplete;
+  const isLive = isLiveStatus(statusClass);
+  const isCancellable = isDownloading || isConverting || isDownloadComplete || isLive;
 
   // Display different status text for each stage
   let statusText = statusClass;
@@ -752,6 +900,8 @@ function createDownloadItem(download) {
     statusText = 'Download complete';
   } else if (isConverting) {
     statusText = `Converting ${Math.round(progress)}%`;
+  } else if (isLive) {
+    statusText = liveStatusText(download);
   }
 
   div.innerHTML = `
@@ -761,6 +911,9 @@ function createDownloadItem(download) {
         <div class="download-type">${download.downloadType || 'video'}</div>
       </div>
       <div class="download-status ${statusClass}">${statusText}</div>
+      ${statusClass === 'recording' ? `
+        <button class="download-cancel-btn download-stop-btn" data-id="${download.id}">Stop</button>
+      ` : ''}
       ${isCancellable ? `
         <button class="download-cancel-btn" data-id="${download.id}">Cancel</button>
       ` : ''}
@@ -774,10 +927,15 @@ function createDownloadItem(download) {
   `;
 
   if (isCancellable) {
-    const cancelBtn = div.querySelector('.download-cancel-btn');
+    const cancelBtn = div.querySelector('.download-cancel-btn:not(.download-stop-btn)');
     cancelBtn.addEventListener('click', () => handleCancelDownload(download.id));
   }
 
+  const stopBtn = div.querySelector('.download-stop-btn');
+  if (stopBtn) {
+    stopBtn.addEventListener('click', () => handleStopRecording(download.id));
+  }
+
   return div;
 }
 
@@ -787,7 +945,8 @@ function updateDownloadItemElement(element, download) {
   const isDownloading = statusClass === 'downloading';
   const isConverting = statusClass === 'converting';
   const isDownloadComplete = statusClass === 'download_complete';
-  const isCancellable = isDownloading || isConverting || isDownloadComplete;
+  const isLive = isLiveStatus(statusClass);
+  const isCancellable = isDownloading || isConverting || isDownloadComplete || isLive;
 
   // Update title
   const titleElement = element.querySelector('.download-title');
@@ -807,6 +966,8 @@ function updateDownloadItemElement(element, download) {
       statusText = 'Download complete';
     } else if (isConverting) {
       statusText = `Converting ${Math.round(progress)}%`;
+    } else if (isLive) {
+      statusText = liveStatusText(download);
     }
     statusElement.textContent = statusText;
   }
@@ -823,8 +984,22 @@ function updateDownloadItemElement(element, download) {
     progressText.textContent = `${Math.round(progress)}%`;
   }
 
+  // Update stop button visibility (live recordings only)
+  let stopBtn = element.querySelector('.download-stop-btn');
+  if (statusClass === 'recording' && !stopBtn) {
+    const header = element.querySelector('.download-header');
+    stopBtn = document.createElement('button');
+    stopBtn.className = 'download-cancel-btn download-stop-btn';
+    stopBtn.dataset.id = download.id;
+    stopBtn.textContent = 'Stop';
+    stopBtn.addEventListener('click', () => handleStopRecording(download.id));
+    header.insertBefore(stopBtn, element.querySelector('.download-cancel-btn'));
+  } else if (statusClass !== 'recording' && stopBtn) {
+    stopBtn.remove();
+  }
+
   // Update cancel button visibility
-  let cancelBtn = element.querySelector('.download-cancel-btn');
+  let cancelBtn = element.querySelector('.download-cancel-btn:not(.download-stop-btn)');
 
   if (isCancellable && !cancelBtn) {
     // Add cancel button if it doesn't exist
@@ -857,6 +1032,34 @@ async function handleCancelDownload(downloadId) {
   }
 }
 
+function isLiveStatus(status) {
+  return status === 'waiting' || status === 'recording' || status === 'stopping';
+}
+
+function liveStatusText(download) {
+  if (download.status === 'waiting') {
+    return 'Waiting for stream';
+  }
+
+  const elapsed = download.elapsed || 0;
+  const hours = Math.floor(elapsed / 3600);
+  const minutes = String(Math.floor((elapsed % 3600) / 60)).padStart(2, '0');
+  const seconds = String(elapsed % 60).padStart(2, '0');
+  const megabytes = ((download.recordedBytes || 0) / (1024 * 1024)).toFixed(1);
+  const label = download.status === 'stopping' ? 'Finalizing' : 'Recording';
+
+  return `${label} ${hours}:${minutes}:${seconds} · ${megabytes} MB`;
+}
+
+async function handleStopRecording(downloadId) {
+  try {
+    await invoke('stop_recording', { downloadId });
+    showToast('Stopping recording...', 'info');
+  } catch (error) {
+    showToast(`${error}`, 'error');
+  }
+}
+
 async function handleCancelDownloads() {
   const downloads = Array.from(state.activeDownloads.keys());
 